@@ -0,0 +1,66 @@
+//! Times `Scanner::scan_tokens` on generated scripts of doubling size.
+//!
+//! Run with `cargo bench --bench scanner`. Scanning is linear when the time
+//! per KB stays roughly flat as the input grows.
+
+use std::time::{Duration, Instant};
+
+use lux::scanner::Scanner;
+
+const CHUNK: &str = r#"// A comment with ünïcödé and emoji 🎉
+class Point {
+  init(x, y) { this.x = x; this.y = y; }
+  norm() { return this.x * this.x + this.y * this.y; }
+}
+fun greet(name, count) {
+  var label = "Hello ${name}, you have ${count + 1} items\n";
+  var raw = r"C:\path\to\file";
+  var table = {"a": 1, "b": [1, 2.5, 3]};
+  if (count >= 10 and name != nil) { return label; }
+  for (var i = 0; i < count; i = i + 1) { print "第${i}行"; }
+  return (x) => x + 1;
+}
+"#;
+
+const RUNS: u32 = 5;
+
+fn source_of_size(bytes: usize) -> String {
+    let mut source = String::with_capacity(bytes + CHUNK.len());
+    while source.len() < bytes {
+        source.push_str(CHUNK);
+    }
+    source
+}
+
+// Best of several runs, to keep scheduler noise out of the comparison.
+fn time_scan(source: &str) -> (Duration, usize) {
+    let mut best = Duration::MAX;
+    let mut tokens = 0;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let mut scanner = Scanner::new(source.to_string());
+        tokens = scanner.scan_tokens().len();
+        best = best.min(start.elapsed());
+    }
+    (best, tokens)
+}
+
+fn main() {
+    println!(
+        "{:>10} {:>10} {:>12} {:>12}",
+        "size (KB)", "tokens", "time (ms)", "us per KB"
+    );
+
+    for kb in [64, 128, 256, 512, 1024, 2048] {
+        let source = source_of_size(kb * 1024);
+        let (time, tokens) = time_scan(&source);
+        let per_kb = time.as_secs_f64() * 1e6 / (source.len() as f64 / 1024.0);
+        println!(
+            "{:>10} {:>10} {:>12.2} {:>12.2}",
+            source.len() / 1024,
+            tokens,
+            time.as_secs_f64() * 1e3,
+            per_kb
+        );
+    }
+}
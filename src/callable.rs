@@ -1,6 +1,7 @@
 use crate::{
     interpreter::{Interpreter, RuntimeResult},
     literal::Literal,
+    token::Token,
 };
 use core::fmt;
 
@@ -9,6 +10,7 @@ pub trait LuxCallable: LuxCallableClone {
         &self,
         interpreter: &mut Interpreter,
         arguments: Vec<Literal>,
+        paren: &Token,
     ) -> RuntimeResult<Literal>;
     fn to_str(&self) -> String;
 
@@ -0,0 +1,191 @@
+use std::rc::Rc;
+
+use crate::{literal::Literal, object::FunctionProto, span::Span, token::Token};
+
+macro_rules! opcodes {
+    ($($(#[$doc:meta])* $name:ident,)*) => {
+        /// An instruction for the VM. Operands follow the opcode in the chunk;
+        /// two- and four-byte operands are big-endian.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        #[repr(u8)]
+        pub enum OpCode {
+            $($(#[$doc])* $name,)*
+        }
+
+        impl OpCode {
+            const ALL: &'static [OpCode] = &[$(OpCode::$name,)*];
+
+            pub fn from_byte(byte: u8) -> OpCode {
+                Self::ALL[byte as usize]
+            }
+        }
+    };
+}
+
+opcodes! {
+    /// Pushes the constant a two-byte index names.
+    Constant,
+    Nil,
+    True,
+    False,
+    Pop,
+    /// Pushes the local in the slot a two-byte operand names.
+    GetLocal,
+    SetLocal,
+    /// Pushes the global named by a two-byte constant index.
+    GetGlobal,
+    DefineGlobal,
+    SetGlobal,
+    /// Pushes the current closure's upvalue a two-byte operand names.
+    GetUpvalue,
+    SetUpvalue,
+    /// Moves the local on top of the stack into the upvalues capturing it,
+    /// then pops it.
+    CloseUpvalue,
+    /// Replaces an object with its property named by a two-byte constant
+    /// index.
+    GetProperty,
+    SetProperty,
+    /// Pops the superclass and `this`, and pushes the superclass method named
+    /// by a two-byte constant index, bound to `this`.
+    GetSuper,
+    GetIndex,
+    SetIndex,
+    Equal,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Not,
+    Negate,
+    Print,
+    /// Jumps forward by a four-byte offset.
+    Jump,
+    /// Jumps forward by a four-byte offset if the top of the stack is falsey,
+    /// without popping it.
+    JumpIfFalse,
+    /// Jumps back by a four-byte offset.
+    Loop,
+    /// Calls the value below as many arguments as a one-byte operand says.
+    Call,
+    /// Makes a closure of the function a two-byte index names, followed by a
+    /// local flag byte and a two-byte index for each variable it captures.
+    Closure,
+    Return,
+    /// Pushes a new class named by a two-byte constant index.
+    Class,
+    /// Copies the methods of the superclass below the class on top of the
+    /// stack into it, and pops the class.
+    Inherit,
+    /// Adds the closure on top of the stack to the class below it as the
+    /// method named by a two-byte constant index.
+    Method,
+    /// Replaces as many values as a two-byte operand says with a list of them.
+    List,
+    /// Replaces as many key and value pairs as a two-byte operand says with a
+    /// map of them.
+    Map,
+    /// Replaces as many values as a two-byte operand says with a string
+    /// joining them.
+    Interpolate,
+    Throw,
+    /// Installs a handler that an error jumps to, a four-byte offset ahead,
+    /// with the error's value pushed.
+    PushCatch,
+    /// Installs a handler that an error jumps to, a four-byte offset ahead,
+    /// with the error set aside for `Rethrow`.
+    PushFinally,
+    PopHandler,
+    /// Raises the error the innermost `PushFinally` handler set aside again.
+    Rethrow,
+    /// Forgets the error the innermost `PushFinally` handler set aside.
+    DropCaught,
+    /// Runs the module at the path constant a two-byte index names, unless it
+    /// has already run, and pushes it.
+    Import,
+    /// Pushes the member of the module on top of the stack named by a
+    /// two-byte constant index.
+    ImportName,
+}
+
+/// The source of an instruction that can fail, for its error message.
+#[derive(Clone, Debug)]
+pub struct Location {
+    pub offset: usize,
+    pub token: Token,
+    pub span: Span,
+    /// A hint for an undefined global, from the locals in scope where it was
+    /// used.
+    pub help: Option<String>,
+}
+
+/// The compiled code of one function.
+#[derive(Default)]
+pub struct Chunk {
+    pub code: Vec<u8>,
+    pub constants: Vec<Literal>,
+    /// The functions declared directly in this one, for `Closure` to make
+    /// closures of.
+    pub functions: Vec<Rc<FunctionProto>>,
+    // The offset each run of instructions from the same line starts at, and
+    // the line.
+    lines: Vec<(usize, usize)>,
+    locations: Vec<Location>,
+}
+
+impl Chunk {
+    pub fn write(&mut self, byte: u8, line: usize) {
+        if self.lines.last().map(|(_, last)| *last) != Some(line) {
+            self.lines.push((self.code.len(), line));
+        }
+        self.code.push(byte);
+    }
+
+    pub fn write_u16(&mut self, value: u16, line: usize) {
+        self.write((value >> 8) as u8, line);
+        self.write(value as u8, line);
+    }
+
+    pub fn write_u32(&mut self, value: u32, line: usize) {
+        for byte in value.to_be_bytes() {
+            self.write(byte, line);
+        }
+    }
+
+    pub fn read_u16(&self, offset: usize) -> u16 {
+        u16::from(self.code[offset]) << 8 | u16::from(self.code[offset + 1])
+    }
+
+    pub fn read_u32(&self, offset: usize) -> u32 {
+        let bytes = &self.code[offset..offset + 4];
+        u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    /// Records where the next instruction written comes from.
+    pub fn locate(&mut self, token: &Token, span: Span, help: Option<String>) {
+        self.locations.push(Location {
+            offset: self.code.len(),
+            token: token.clone(),
+            span,
+            help,
+        });
+    }
+
+    /// The location of the last located instruction starting before `ip`,
+    /// which is the running one while `ip` is inside it.
+    pub fn location(&self, ip: usize) -> &Location {
+        let index = self
+            .locations
+            .partition_point(|location| location.offset < ip);
+        &self.locations[index - 1]
+    }
+
+    pub fn line(&self, offset: usize) -> usize {
+        let index = self.lines.partition_point(|(start, _)| *start <= offset);
+        self.lines[index - 1].1
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
+
+use crate::{
+    callable::LuxCallable,
+    function::LuxFunction,
+    instance::LuxInstance,
+    interpreter::{Interpreter, RuntimeResult},
+    literal::Literal,
+    token::Token,
+};
+use rand::Rng;
+
+#[derive(Clone, Debug, Eq)]
+pub struct LuxClass {
+    pub name: String,
+    superclass: Option<Box<LuxClass>>,
+    methods: Rc<HashMap<String, LuxFunction>>,
+    id: usize,
+}
+
+impl LuxCallable for LuxClass {
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Literal>,
+        paren: &Token,
+    ) -> RuntimeResult<Literal> {
+        let instance = Literal::Instance(Box::new(LuxInstance::new(self.clone())));
+
+        if let Some(initializer) = self.find_method("init") {
+            initializer
+                .bind(instance.clone())
+                .call(interpreter, arguments, paren)?;
+        }
+
+        Ok(instance)
+    }
+
+    fn to_str(&self) -> String {
+        self.name.clone()
+    }
+
+    fn arity(&self) -> usize {
+        match self.find_method("init") {
+            Some(initializer) => initializer.arity(),
+            None => 0,
+        }
+    }
+
+    fn id(&self) -> usize {
+        self.id
+    }
+}
+
+impl LuxClass {
+    pub fn new(
+        name: String,
+        superclass: Option<LuxClass>,
+        methods: HashMap<String, LuxFunction>,
+    ) -> Self {
+        let mut rng = rand::thread_rng();
+        let id: usize = rng.gen();
+        Self {
+            name,
+            superclass: superclass.map(Box::new),
+            methods: Rc::new(methods),
+            id,
+        }
+    }
+
+    pub fn find_method(&self, name: &str) -> Option<&LuxFunction> {
+        match self.methods.get(name) {
+            Some(method) => Some(method),
+            None => self
+                .superclass
+                .as_ref()
+                .and_then(|superclass| superclass.find_method(name)),
+        }
+    }
+}
+
+impl PartialEq for LuxClass {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Hash for LuxClass {
+    fn hash<H: Hasher>(&self, hasher: &mut H) {
+        self.id.hash(hasher);
+    }
+}
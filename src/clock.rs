@@ -9,6 +9,7 @@ use crate::{
     callable::LuxCallable,
     interpreter::{Interpreter, RuntimeResult},
     literal::{Float, Literal},
+    token::Token,
 };
 #[derive(Clone, Debug, Eq)]
 pub struct Clock {
@@ -17,7 +18,7 @@ pub struct Clock {
 }
 
 impl LuxCallable for Clock {
-    fn call(&self, _: &mut Interpreter, _: Vec<Literal>) -> RuntimeResult<Literal> {
+    fn call(&self, _: &mut Interpreter, _: Vec<Literal>, _: &Token) -> RuntimeResult<Literal> {
         let start = SystemTime::now();
         let since_the_epoch = start
             .duration_since(UNIX_EPOCH)
@@ -0,0 +1,934 @@
+use std::{collections::HashMap, convert::TryFrom, path::Path, rc::Rc};
+
+use crate::{
+    chunk::{Chunk, OpCode},
+    error::LuxError,
+    error_code::ErrorCode,
+    expr::Expr,
+    function::Function,
+    literal::Literal,
+    object::{FunctionKind, FunctionProto},
+    span::Span,
+    stmt::Stmt,
+    suggestion::did_you_mean,
+    token::Token,
+    token_type::Types,
+};
+
+type CompileResult<T = ()> = Result<T, LuxError>;
+
+struct Local {
+    name: String,
+    depth: usize,
+    captured: bool,
+}
+
+/// Where a closure finds a variable it captures: in a local slot of the
+/// function around it, or among that function's own upvalues.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct UpvalueRef {
+    index: u16,
+    is_local: bool,
+}
+
+/// Something a `break`, `continue` or `return` has to clean up after when it
+/// jumps out of it.
+enum Exit<'a> {
+    /// A loop, with the number of locals declared outside it, and the jumps
+    /// out of it to patch once its end is known.
+    Loop {
+        locals: usize,
+        breaks: Vec<usize>,
+        continues: Vec<usize>,
+    },
+    /// A try or catch block: its handler has to be removed, and its `finally`
+    /// run.
+    Handler { finally: Option<&'a [Stmt]> },
+    /// A `finally` block run because of an error, which is set aside until
+    /// the block ends.
+    PendingError,
+}
+
+struct FunctionState<'a> {
+    name: Option<String>,
+    kind: FunctionKind,
+    arity: usize,
+    chunk: Chunk,
+    constants: HashMap<Literal, u16>,
+    locals: Vec<Local>,
+    upvalues: Vec<UpvalueRef>,
+    scope_depth: usize,
+    exits: Vec<Exit<'a>>,
+    line: usize,
+}
+
+impl<'a> FunctionState<'a> {
+    fn new(name: Option<String>, kind: FunctionKind, line: usize) -> Self {
+        // Slot 0 holds the function being called, or the instance a method
+        // is bound to.
+        let receiver = match kind {
+            FunctionKind::Method | FunctionKind::Initializer => "this",
+            _ => "",
+        };
+        FunctionState {
+            name,
+            kind,
+            arity: 0,
+            chunk: Chunk::default(),
+            constants: HashMap::new(),
+            locals: vec![Local {
+                name: receiver.to_string(),
+                depth: 0,
+                captured: false,
+            }],
+            upvalues: Vec::new(),
+            scope_depth: 0,
+            exits: Vec::new(),
+            line,
+        }
+    }
+
+    fn resolve_local(&self, name: &str) -> Option<usize> {
+        self.locals.iter().rposition(|local| local.name == name)
+    }
+}
+
+enum Variable {
+    Local(u16),
+    Upvalue(u16),
+    Global(u16),
+}
+
+/// Compiles resolved statements into bytecode for the VM. Locals live in
+/// stack slots, and variables closures capture are found the way the
+/// resolver found them: by looking outward one function at a time.
+pub struct Compiler<'a> {
+    functions: Vec<FunctionState<'a>>,
+    file: Rc<Path>,
+    // The offset each line of the source starts at, so statements without
+    // tokens of their own still get the right line.
+    line_starts: Vec<usize>,
+    // The statement being compiled, to point errors at.
+    span: Span,
+    token: Option<Token>,
+}
+
+impl<'a> Compiler<'a> {
+    /// Compiles the top level of the script or module in `file`, which
+    /// `statements` were parsed from `source`.
+    pub fn compile(
+        statements: &'a [Stmt],
+        file: Rc<Path>,
+        source: &str,
+    ) -> CompileResult<Rc<FunctionProto>> {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+        let mut compiler = Compiler {
+            functions: vec![FunctionState::new(None, FunctionKind::Script, 1)],
+            file,
+            line_starts,
+            span: Span::default(),
+            token: None,
+        };
+        for statement in statements {
+            compiler.statement(statement)?;
+        }
+        compiler.emit_return();
+        Ok(compiler.end_function().0)
+    }
+
+    fn statement(&mut self, statement: &'a Stmt) -> CompileResult {
+        self.span = statement.span();
+        let line = self.line_at(self.span.start);
+        self.state().line = line;
+        match statement {
+            Stmt::Expression { expression, .. } => {
+                self.expression(expression)?;
+                self.emit(OpCode::Pop);
+            }
+            Stmt::Print { expression, .. } => {
+                self.expression(expression)?;
+                self.emit(OpCode::Print);
+            }
+            Stmt::Var {
+                name, initializer, ..
+            } => {
+                self.at(name);
+                self.expression(initializer)?;
+                self.define(name)?;
+            }
+            Stmt::Block { statements, .. } => self.block(statements)?,
+            Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.expression(condition)?;
+                let then_jump = self.emit_jump(OpCode::JumpIfFalse);
+                self.emit(OpCode::Pop);
+                self.statement(then_branch)?;
+                let else_jump = self.emit_jump(OpCode::Jump);
+                self.patch_jump(then_jump)?;
+                self.emit(OpCode::Pop);
+                if let Some(else_branch) = else_branch {
+                    self.statement(else_branch)?;
+                }
+                self.patch_jump(else_jump)?;
+            }
+            Stmt::While {
+                condition,
+                body,
+                increment,
+                ..
+            } => {
+                let start = self.chunk().code.len();
+                self.expression(condition)?;
+                let exit = self.emit_jump(OpCode::JumpIfFalse);
+                self.emit(OpCode::Pop);
+
+                let locals = self.state().locals.len();
+                self.state().exits.push(Exit::Loop {
+                    locals,
+                    breaks: Vec::new(),
+                    continues: Vec::new(),
+                });
+                self.statement(body)?;
+                let (breaks, continues) = match self.state().exits.pop() {
+                    Some(Exit::Loop {
+                        breaks, continues, ..
+                    }) => (breaks, continues),
+                    _ => unreachable!("loop exit replaced"),
+                };
+
+                for jump in continues {
+                    self.patch_jump(jump)?;
+                }
+                if let Some(increment) = increment {
+                    self.expression(increment)?;
+                    self.emit(OpCode::Pop);
+                }
+                self.emit_loop(start)?;
+
+                self.patch_jump(exit)?;
+                self.emit(OpCode::Pop);
+                for jump in breaks {
+                    self.patch_jump(jump)?;
+                }
+            }
+            Stmt::Break { keyword, .. } => {
+                self.at(keyword);
+                self.jump_out_of_loop(false)?;
+            }
+            Stmt::Continue { keyword, .. } => {
+                self.at(keyword);
+                self.jump_out_of_loop(true)?;
+            }
+            Stmt::Return { keyword, value, .. } => {
+                self.at(keyword);
+                if self.state().kind == FunctionKind::Initializer {
+                    self.emit_op_u16(OpCode::GetLocal, 0);
+                } else {
+                    self.expression(value)?;
+                }
+                // The value waits on the stack while `finally` blocks run.
+                self.state().locals.push(Local {
+                    name: String::new(),
+                    depth: 0,
+                    captured: false,
+                });
+                self.leave_exits(0)?;
+                self.state().locals.pop();
+                self.emit(OpCode::Return);
+            }
+            Stmt::Throw { keyword, value, .. } => {
+                self.at(keyword);
+                self.expression(value)?;
+                self.chunk().locate(keyword, keyword.span, None);
+                self.emit(OpCode::Throw);
+            }
+            Stmt::Try {
+                body,
+                catch,
+                finally,
+                ..
+            } => self.try_statement(body, catch, finally.as_deref())?,
+            Stmt::Function(function) => {
+                let name = function.declared_name();
+                self.at(name);
+                if self.state().scope_depth > 0 {
+                    // Declared before its body is compiled, so the body can
+                    // call it.
+                    self.add_local(&name.lexeme)?;
+                    self.function(function, FunctionKind::Function)?;
+                } else {
+                    self.function(function, FunctionKind::Function)?;
+                    self.define(name)?;
+                }
+            }
+            Stmt::Class {
+                name,
+                superclass,
+                methods,
+                ..
+            } => {
+                self.at(name);
+                let constant = self.name(&name.lexeme)?;
+                self.emit_op_u16(OpCode::Class, constant);
+                self.define(name)?;
+
+                if let Some(superclass) = superclass {
+                    self.expression(superclass)?;
+                    self.begin_scope();
+                    self.add_local("super")?;
+                    self.get_variable(name)?;
+                    let token = match superclass.as_ref() {
+                        Expr::Variable { name, .. } => name,
+                        _ => name,
+                    };
+                    self.chunk().locate(token, token.span, None);
+                    self.emit(OpCode::Inherit);
+                }
+
+                self.get_variable(name)?;
+                for method in methods {
+                    let method_name = &method.declared_name().lexeme;
+                    let kind = if method_name == "init" {
+                        FunctionKind::Initializer
+                    } else {
+                        FunctionKind::Method
+                    };
+                    self.function(method, kind)?;
+                    let constant = self.name(method_name)?;
+                    self.emit_op_u16(OpCode::Method, constant);
+                }
+                self.emit(OpCode::Pop);
+
+                if superclass.is_some() {
+                    self.end_scope();
+                }
+            }
+            Stmt::Import {
+                path, alias, names, ..
+            } => {
+                self.at(path);
+                let constant = self.constant(path.literal.clone())?;
+                self.chunk().locate(path, path.span, None);
+                self.emit_op_u16(OpCode::Import, constant);
+
+                if let Some(alias) = alias {
+                    self.define(alias)?;
+                } else {
+                    for name in names {
+                        let constant = self.name(&name.lexeme)?;
+                        self.chunk().locate(name, name.span, None);
+                        self.emit_op_u16(OpCode::ImportName, constant);
+                        self.define(name)?;
+                    }
+                    self.emit(OpCode::Pop);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn block(&mut self, statements: &'a [Stmt]) -> CompileResult {
+        self.begin_scope();
+        for statement in statements {
+            self.statement(statement)?;
+        }
+        self.end_scope();
+        Ok(())
+    }
+
+    // The try block runs under a handler. An error jumps to the catch block
+    // with its value in the catch variable's slot, or, without one, to a copy
+    // of the finally block that raises the error again after it. The finally
+    // block is also copied after the try and catch blocks, and before every
+    // jump out of them.
+    fn try_statement(
+        &mut self,
+        body: &'a [Stmt],
+        catch: &'a Option<(Token, Vec<Stmt>)>,
+        finally: Option<&'a [Stmt]>,
+    ) -> CompileResult {
+        let handler = if catch.is_some() {
+            self.emit_jump(OpCode::PushCatch)
+        } else {
+            self.emit_jump(OpCode::PushFinally)
+        };
+        self.state().exits.push(Exit::Handler { finally });
+        self.block(body)?;
+        self.state().exits.pop();
+        self.emit(OpCode::PopHandler);
+        if let Some(finally) = finally {
+            self.block(finally)?;
+        }
+        let end = self.emit_jump(OpCode::Jump);
+        self.patch_jump(handler)?;
+
+        match (catch, finally) {
+            (Some((name, catch_body)), Some(finally)) => {
+                self.begin_scope();
+                self.add_local(&name.lexeme)?;
+                let handler = self.emit_jump(OpCode::PushFinally);
+                self.state().exits.push(Exit::Handler {
+                    finally: Some(finally),
+                });
+                for statement in catch_body {
+                    self.statement(statement)?;
+                }
+                self.state().exits.pop();
+                self.emit(OpCode::PopHandler);
+                self.end_scope();
+                self.block(finally)?;
+                let catch_end = self.emit_jump(OpCode::Jump);
+
+                // An error in the catch block leaves the catch variable on
+                // the stack.
+                self.patch_jump(handler)?;
+                self.emit(OpCode::CloseUpvalue);
+                self.pending_finally(finally)?;
+                self.patch_jump(catch_end)?;
+            }
+            (Some((name, catch_body)), None) => {
+                self.begin_scope();
+                self.add_local(&name.lexeme)?;
+                for statement in catch_body {
+                    self.statement(statement)?;
+                }
+                self.end_scope();
+            }
+            (None, Some(finally)) => self.pending_finally(finally)?,
+            (None, None) => {}
+        }
+
+        self.patch_jump(end)
+    }
+
+    fn pending_finally(&mut self, finally: &'a [Stmt]) -> CompileResult {
+        self.state().exits.push(Exit::PendingError);
+        self.block(finally)?;
+        self.state().exits.pop();
+        self.emit(OpCode::Rethrow);
+        Ok(())
+    }
+
+    fn jump_out_of_loop(&mut self, continuing: bool) -> CompileResult {
+        let index = self
+            .state()
+            .exits
+            .iter()
+            .rposition(|exit| matches!(exit, Exit::Loop { .. }))
+            .expect("the resolver only allows 'break' and 'continue' in loops");
+        self.leave_exits(index + 1)?;
+
+        let locals = match &self.state().exits[index] {
+            Exit::Loop { locals, .. } => *locals,
+            _ => unreachable!(),
+        };
+        self.pop_locals(locals);
+        let jump = self.emit_jump(OpCode::Jump);
+        match &mut self.state().exits[index] {
+            Exit::Loop { continues, .. } if continuing => continues.push(jump),
+            Exit::Loop { breaks, .. } => breaks.push(jump),
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    // Cleans up after the exits from the innermost down to `outermost`,
+    // before jumping out of them.
+    fn leave_exits(&mut self, outermost: usize) -> CompileResult {
+        for index in (outermost..self.state().exits.len()).rev() {
+            match self.state().exits[index] {
+                Exit::Handler { finally } => {
+                    self.emit(OpCode::PopHandler);
+                    if let Some(finally) = finally {
+                        // Jumps out of the copy only clean up after what is
+                        // outside this exit.
+                        let inner = self.state().exits.split_off(index);
+                        self.block(finally)?;
+                        self.state().exits.extend(inner);
+                    }
+                }
+                Exit::PendingError => self.emit(OpCode::DropCaught),
+                Exit::Loop { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn function(&mut self, function: &'a Function, kind: FunctionKind) -> CompileResult {
+        let name = function.name.as_ref().map(|name| name.lexeme.clone());
+        let line = self.line_at(function.span.start);
+        self.functions.push(FunctionState::new(name, kind, line));
+        self.begin_scope();
+        for param in &function.param {
+            self.add_local(&param.lexeme)?;
+        }
+        self.state().arity = function.param.len();
+        for statement in &function.body {
+            self.statement(statement)?;
+        }
+        self.emit_return();
+
+        let (proto, upvalues) = self.end_function();
+        let count = self.chunk().functions.len();
+        let index = self.index(count, "Too many functions in one chunk.")?;
+        self.chunk().functions.push(proto);
+        self.emit_op_u16(OpCode::Closure, index);
+        for upvalue in upvalues {
+            self.emit_byte(u8::from(upvalue.is_local));
+            self.emit_u16(upvalue.index);
+        }
+        Ok(())
+    }
+
+    fn end_function(&mut self) -> (Rc<FunctionProto>, Vec<UpvalueRef>) {
+        let state = self.functions.pop().expect("no function being compiled");
+        let proto = FunctionProto {
+            name: state.name,
+            kind: state.kind,
+            arity: state.arity,
+            upvalues: state.upvalues.len(),
+            chunk: state.chunk,
+            file: self.file.clone(),
+        };
+        (Rc::new(proto), state.upvalues)
+    }
+
+    fn expression(&mut self, expr: &'a Expr) -> CompileResult {
+        match expr {
+            Expr::Binary {
+                left,
+                right,
+                operator,
+                ..
+            } => {
+                self.expression(left)?;
+                self.expression(right)?;
+                let op = match operator.type_t {
+                    Types::PLUS => OpCode::Add,
+                    Types::MINUS => OpCode::Subtract,
+                    Types::STAR => OpCode::Multiply,
+                    Types::SLASH => OpCode::Divide,
+                    Types::GREATER => OpCode::Greater,
+                    Types::GREATER_EQUAL => OpCode::GreaterEqual,
+                    Types::LESS => OpCode::Less,
+                    Types::LESS_EQUAL => OpCode::LessEqual,
+                    Types::EQUAL_EQUAL => OpCode::Equal,
+                    Types::BANG_EQUAL => {
+                        self.emit(OpCode::Equal);
+                        self.emit(OpCode::Not);
+                        return Ok(());
+                    }
+                    _ => unreachable!("not a binary operator: {:?}", operator.type_t),
+                };
+                self.at(operator);
+                self.chunk().locate(operator, expr.span(), None);
+                self.emit(op);
+            }
+            Expr::Grouping { expression, .. } => self.expression(expression)?,
+            Expr::Literal { value, .. } => match value {
+                Literal::Nil => self.emit(OpCode::Nil),
+                Literal::Bool(true) => self.emit(OpCode::True),
+                Literal::Bool(false) => self.emit(OpCode::False),
+                _ => {
+                    let constant = self.constant(value.clone())?;
+                    self.emit_op_u16(OpCode::Constant, constant);
+                }
+            },
+            Expr::Unary {
+                operator, right, ..
+            } => {
+                self.expression(right)?;
+                self.at(operator);
+                if operator.type_t == Types::MINUS {
+                    self.chunk().locate(operator, expr.span(), None);
+                    self.emit(OpCode::Negate);
+                } else {
+                    self.emit(OpCode::Not);
+                }
+            }
+            Expr::Variable { name, .. } => self.get_variable(name)?,
+            Expr::Assign { name, value, .. } => {
+                self.expression(value)?;
+                self.at(name);
+                match self.variable(&name.lexeme)? {
+                    Variable::Local(slot) => self.emit_op_u16(OpCode::SetLocal, slot),
+                    Variable::Upvalue(index) => self.emit_op_u16(OpCode::SetUpvalue, index),
+                    Variable::Global(constant) => {
+                        let help = self.suggest_local(&name.lexeme);
+                        self.chunk().locate(name, name.span, help);
+                        self.emit_op_u16(OpCode::SetGlobal, constant);
+                    }
+                }
+            }
+            Expr::Logical {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                self.expression(left)?;
+                if operator.type_t == Types::OR {
+                    let else_jump = self.emit_jump(OpCode::JumpIfFalse);
+                    let end = self.emit_jump(OpCode::Jump);
+                    self.patch_jump(else_jump)?;
+                    self.emit(OpCode::Pop);
+                    self.expression(right)?;
+                    self.patch_jump(end)?;
+                } else {
+                    let end = self.emit_jump(OpCode::JumpIfFalse);
+                    self.emit(OpCode::Pop);
+                    self.expression(right)?;
+                    self.patch_jump(end)?;
+                }
+            }
+            Expr::Call {
+                callee,
+                paren,
+                arguments,
+                ..
+            } => {
+                self.expression(callee)?;
+                for argument in arguments {
+                    self.expression(argument)?;
+                }
+                self.at(paren);
+                // Errors about the callee point at it; the span of the whole
+                // call is this one extended to the paren.
+                self.chunk().locate(paren, callee.span(), None);
+                self.emit(OpCode::Call);
+                self.emit_byte(arguments.len() as u8);
+            }
+            Expr::Get { object, name, .. } => {
+                self.expression(object)?;
+                self.property(OpCode::GetProperty, name)?;
+            }
+            Expr::Set {
+                object,
+                name,
+                value,
+                ..
+            } => {
+                self.expression(object)?;
+                self.expression(value)?;
+                self.property(OpCode::SetProperty, name)?;
+            }
+            Expr::This { keyword, .. } => self.get_variable(keyword)?,
+            Expr::Super {
+                keyword, method, ..
+            } => {
+                self.at(keyword);
+                self.get_named("this")?;
+                self.get_named("super")?;
+                self.property(OpCode::GetSuper, method)?;
+            }
+            Expr::List { elements, .. } => {
+                for element in elements {
+                    self.expression(element)?;
+                }
+                let count = self.index(elements.len(), "Too many elements in a list.")?;
+                self.emit_op_u16(OpCode::List, count);
+            }
+            Expr::Map { brace, entries, .. } => {
+                for (key, value) in entries {
+                    self.expression(key)?;
+                    self.expression(value)?;
+                }
+                let count = self.index(entries.len(), "Too many entries in a map.")?;
+                self.chunk().locate(brace, brace.span, None);
+                self.emit_op_u16(OpCode::Map, count);
+            }
+            Expr::Index {
+                object,
+                bracket,
+                index,
+                ..
+            } => {
+                self.expression(object)?;
+                self.expression(index)?;
+                self.at(bracket);
+                self.chunk().locate(bracket, bracket.span, None);
+                self.emit(OpCode::GetIndex);
+            }
+            Expr::SetIndex {
+                object,
+                bracket,
+                index,
+                value,
+                ..
+            } => {
+                self.expression(object)?;
+                self.expression(index)?;
+                self.expression(value)?;
+                self.at(bracket);
+                self.chunk().locate(bracket, bracket.span, None);
+                self.emit(OpCode::SetIndex);
+            }
+            Expr::Lambda { function, .. } => self.function(function, FunctionKind::Function)?,
+            Expr::Interpolation { parts, .. } => {
+                for part in parts {
+                    self.expression(part)?;
+                }
+                let count = self.index(parts.len(), "Too many parts in a string.")?;
+                self.emit_op_u16(OpCode::Interpolate, count);
+            }
+            Expr::Nil => self.emit(OpCode::Nil),
+        }
+        Ok(())
+    }
+
+    fn property(&mut self, op: OpCode, name: &Token) -> CompileResult {
+        self.at(name);
+        let constant = self.name(&name.lexeme)?;
+        self.chunk().locate(name, name.span, None);
+        self.emit_op_u16(op, constant);
+        Ok(())
+    }
+
+    fn get_variable(&mut self, name: &Token) -> CompileResult {
+        self.at(name);
+        match self.variable(&name.lexeme)? {
+            Variable::Local(slot) => self.emit_op_u16(OpCode::GetLocal, slot),
+            Variable::Upvalue(index) => self.emit_op_u16(OpCode::GetUpvalue, index),
+            Variable::Global(constant) => {
+                let help = self.suggest_local(&name.lexeme);
+                self.chunk().locate(name, name.span, help);
+                self.emit_op_u16(OpCode::GetGlobal, constant);
+            }
+        }
+        Ok(())
+    }
+
+    // Loads `this` or `super`, which the resolver made sure are in scope.
+    fn get_named(&mut self, name: &str) -> CompileResult {
+        match self.variable(name)? {
+            Variable::Local(slot) => self.emit_op_u16(OpCode::GetLocal, slot),
+            Variable::Upvalue(index) => self.emit_op_u16(OpCode::GetUpvalue, index),
+            Variable::Global(_) => unreachable!("'{}' is always local", name),
+        }
+        Ok(())
+    }
+
+    fn variable(&mut self, name: &str) -> CompileResult<Variable> {
+        let current = self.functions.len() - 1;
+        if let Some(slot) = self.functions[current].resolve_local(name) {
+            return Ok(Variable::Local(slot as u16));
+        }
+        if let Some(index) = self.resolve_upvalue(current, name)? {
+            return Ok(Variable::Upvalue(index));
+        }
+        Ok(Variable::Global(self.name(name)?))
+    }
+
+    fn resolve_upvalue(&mut self, function: usize, name: &str) -> CompileResult<Option<u16>> {
+        if function == 0 {
+            return Ok(None);
+        }
+        let enclosing = function - 1;
+        if let Some(slot) = self.functions[enclosing].resolve_local(name) {
+            self.functions[enclosing].locals[slot].captured = true;
+            let upvalue = UpvalueRef {
+                index: slot as u16,
+                is_local: true,
+            };
+            return self.add_upvalue(function, upvalue).map(Some);
+        }
+        match self.resolve_upvalue(enclosing, name)? {
+            Some(index) => {
+                let upvalue = UpvalueRef {
+                    index,
+                    is_local: false,
+                };
+                self.add_upvalue(function, upvalue).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn add_upvalue(&mut self, function: usize, upvalue: UpvalueRef) -> CompileResult<u16> {
+        let upvalues = &self.functions[function].upvalues;
+        if let Some(index) = upvalues.iter().position(|existing| *existing == upvalue) {
+            return Ok(index as u16);
+        }
+        let index = self.index(upvalues.len(), "Too many closure variables in function.")?;
+        self.functions[function].upvalues.push(upvalue);
+        Ok(index)
+    }
+
+    // The same hint the resolver gives for a name that isn't a local.
+    fn suggest_local(&self, name: &str) -> Option<String> {
+        let locals = self
+            .functions
+            .iter()
+            .flat_map(|function| function.locals.iter())
+            .map(|local| local.name.as_str())
+            .filter(|local| !matches!(*local, "" | "this" | "super"));
+        did_you_mean(name, locals)
+    }
+
+    // Defines a variable whose value is on top of the stack.
+    fn define(&mut self, name: &Token) -> CompileResult {
+        if self.state().scope_depth > 0 {
+            self.add_local(&name.lexeme)
+        } else {
+            let constant = self.name(&name.lexeme)?;
+            self.emit_op_u16(OpCode::DefineGlobal, constant);
+            Ok(())
+        }
+    }
+
+    fn add_local(&mut self, name: &str) -> CompileResult {
+        let count = self.state().locals.len();
+        self.index(count, "Too many local variables in function.")?;
+        let depth = self.state().scope_depth;
+        self.state().locals.push(Local {
+            name: name.to_string(),
+            depth,
+            captured: false,
+        });
+        Ok(())
+    }
+
+    fn begin_scope(&mut self) {
+        self.state().scope_depth += 1;
+    }
+
+    fn end_scope(&mut self) {
+        let state = self.state();
+        state.scope_depth -= 1;
+        let depth = state.scope_depth;
+        let remaining = state
+            .locals
+            .iter()
+            .rposition(|local| local.depth <= depth)
+            .map_or(0, |index| index + 1);
+        self.pop_locals(remaining);
+        self.state().locals.truncate(remaining);
+    }
+
+    // Emits the pops for the locals above the first `count`, leaving them
+    // declared.
+    fn pop_locals(&mut self, count: usize) {
+        for index in (count..self.state().locals.len()).rev() {
+            if self.state().locals[index].captured {
+                self.emit(OpCode::CloseUpvalue);
+            } else {
+                self.emit(OpCode::Pop);
+            }
+        }
+    }
+
+    fn emit_return(&mut self) {
+        if self.state().kind == FunctionKind::Initializer {
+            self.emit_op_u16(OpCode::GetLocal, 0);
+        } else {
+            self.emit(OpCode::Nil);
+        }
+        self.emit(OpCode::Return);
+    }
+
+    fn emit(&mut self, op: OpCode) {
+        self.emit_byte(op as u8);
+    }
+
+    fn emit_byte(&mut self, byte: u8) {
+        let state = self.state();
+        state.chunk.write(byte, state.line);
+    }
+
+    fn emit_u16(&mut self, value: u16) {
+        let state = self.state();
+        state.chunk.write_u16(value, state.line);
+    }
+
+    fn emit_op_u16(&mut self, op: OpCode, operand: u16) {
+        self.emit(op);
+        self.emit_u16(operand);
+    }
+
+    fn emit_jump(&mut self, op: OpCode) -> usize {
+        self.emit(op);
+        let state = self.state();
+        state.chunk.write_u32(u32::MAX, state.line);
+        self.chunk().code.len() - 4
+    }
+
+    fn patch_jump(&mut self, at: usize) -> CompileResult {
+        let offset = self.chunk().code.len() - at - 4;
+        let offset = self.jump_offset(offset, "Too much code to jump over.")?;
+        self.chunk().code[at..at + 4].copy_from_slice(&offset.to_be_bytes());
+        Ok(())
+    }
+
+    fn emit_loop(&mut self, start: usize) -> CompileResult {
+        self.emit(OpCode::Loop);
+        let offset = self.chunk().code.len() - start + 4;
+        let offset = self.jump_offset(offset, "Loop body too large.")?;
+        let state = self.state();
+        state.chunk.write_u32(offset, state.line);
+        Ok(())
+    }
+
+    fn constant(&mut self, value: Literal) -> CompileResult<u16> {
+        if let Some(index) = self.state().constants.get(&value) {
+            return Ok(*index);
+        }
+        let count = self.chunk().constants.len();
+        let index = self.index(count, "Too many constants in one chunk.")?;
+        self.chunk().constants.push(value.clone());
+        self.state().constants.insert(value, index);
+        Ok(index)
+    }
+
+    fn name(&mut self, name: &str) -> CompileResult<u16> {
+        self.constant(Literal::String(name.to_string()))
+    }
+
+    // Checks that `value` fits in a two-byte operand.
+    fn index(&self, value: usize, message: &str) -> CompileResult<u16> {
+        u16::try_from(value).map_err(|_| self.too_many(message))
+    }
+
+    // Checks that `value` fits in the four-byte operand of a jump.
+    fn jump_offset(&self, value: usize, message: &str) -> CompileResult<u32> {
+        u32::try_from(value).map_err(|_| self.too_many(message))
+    }
+
+    fn too_many(&self, message: &str) -> LuxError {
+        let error = match &self.token {
+            Some(token) => LuxError::new(token, ErrorCode::TooMany, message),
+            None => LuxError::new(
+                &Token::new(Types::EOF, String::new(), Literal::Nil, 1, 1, self.span),
+                ErrorCode::TooMany,
+                message,
+            ),
+        };
+        error.with_span(self.span)
+    }
+
+    // The line of the source the byte `offset` is on.
+    fn line_at(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|start| *start <= offset)
+    }
+
+    // Notes that the code being compiled comes from `token`'s line.
+    fn at(&mut self, token: &Token) {
+        self.state().line = token.line;
+        self.token = Some(token.clone());
+    }
+
+    fn state(&mut self) -> &mut FunctionState<'a> {
+        self.functions
+            .last_mut()
+            .expect("no function being compiled")
+    }
+
+    fn chunk(&mut self) -> &mut Chunk {
+        &mut self.state().chunk
+    }
+}
@@ -0,0 +1,349 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use text_colorizer::*;
+
+use crate::{
+    error::LuxError,
+    error_code::ErrorCode,
+    runtime_error::{RuntimeError, TraceLine},
+    span::Span,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    fn name(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+/// How diagnostics are written out: for people, or one JSON object per line
+/// for tools.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+}
+
+/// An error or warning ready to be shown to the user, pointing at the range of
+/// source it is about.
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// A stable code for the kind of diagnostic, see `ErrorCode`.
+    pub code: ErrorCode,
+    pub message: String,
+    /// The file the span points into, when it isn't the script being run.
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+    pub span: Span,
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+    /// The calls in progress when a runtime error was raised, innermost first.
+    pub trace: Vec<TraceLine>,
+}
+
+impl From<&LuxError> for Diagnostic {
+    fn from(error: &LuxError) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            code: error.code,
+            message: error.message.clone(),
+            file: error.file.as_ref().map(|file| file.to_path_buf()),
+            line: error.line,
+            column: error.column,
+            span: error.span,
+            notes: error.notes.clone(),
+            help: None,
+            trace: vec![],
+        }
+    }
+}
+
+impl From<&RuntimeError> for Diagnostic {
+    fn from(error: &RuntimeError) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            code: error.code,
+            message: error.message.clone(),
+            file: error.file.as_ref().map(|file| file.to_path_buf()),
+            line: error.token.line,
+            column: error.token.column,
+            span: error.span,
+            notes: vec![],
+            help: error.help.as_deref().map(str::to_string),
+            trace: error.trace.clone(),
+        }
+    }
+}
+
+impl Diagnostic {
+    /// Renders the diagnostic the way rustc does:
+    ///
+    /// ```text
+    /// error: Operands must be numbers.
+    ///  --> script.lux:2:9
+    ///   |
+    /// 2 | print 1 - "a";
+    ///   |       ^^^^^^^
+    /// ```
+    ///
+    /// `script` and `source` are the file being run, which is used unless the
+    /// diagnostic names another file. Colors are only used when `color` is set.
+    pub fn render(&self, script: &Path, source: &str, color: bool) -> String {
+        let style = Style { color };
+        let file = self.file.as_deref().unwrap_or(script);
+        let text = self.source(script, source);
+
+        let label = match self.severity {
+            Severity::Error => style.paint("error", |s| s.red().bold()),
+            Severity::Warning => style.paint("warning", |s| s.yellow().bold()),
+        };
+        // The span can start before the token the line and column were taken
+        // from, so they are worked out from the span when the source is at
+        // hand.
+        let snippet = text.as_deref().and_then(|t| self.snippet(t));
+        let (line_number, column) = snippet
+            .as_ref()
+            .map_or((self.line, self.column), |s| (s.line_number, s.column));
+        let gutter = " ".repeat(line_number.to_string().len());
+        let bar = style.paint("|", |s| s.blue().bold());
+
+        let mut output = format!(
+            "{}{} {}\n{}{} {}:{}:{}\n",
+            label,
+            style.paint(":", |s| s.bold()),
+            style.paint(&self.message, |s| s.bold()),
+            gutter,
+            style.paint("-->", |s| s.blue().bold()),
+            file.display(),
+            line_number,
+            column
+        );
+
+        if let Some(snippet) = snippet {
+            let carets = "^".repeat(snippet.width);
+            let carets = match self.severity {
+                Severity::Error => style.paint(&carets, |s| s.red().bold()),
+                Severity::Warning => style.paint(&carets, |s| s.yellow().bold()),
+            };
+            output.push_str(&format!("{} {}\n", gutter, bar));
+            output.push_str(&format!(
+                "{} {} {}\n",
+                style.paint(&line_number.to_string(), |s| s.blue().bold()),
+                bar,
+                snippet.line
+            ));
+            output.push_str(&format!(
+                "{} {} {}{}\n",
+                gutter, bar, snippet.padding, carets
+            ));
+        }
+
+        if !self.trace.is_empty() {
+            output.push_str(&format!(
+                "{} {} stack trace, most recent call first:\n",
+                gutter,
+                style.paint("= note:", |s| s.bold())
+            ));
+            output.push_str(&self.render_trace(script, &gutter));
+        }
+
+        for note in &self.notes {
+            output.push_str(&format!(
+                "{} {} {}\n",
+                gutter,
+                style.paint("= note:", |s| s.bold()),
+                note
+            ));
+        }
+        if let Some(help) = &self.help {
+            output.push_str(&format!(
+                "{} {} {}\n",
+                gutter,
+                style.paint("= help:", |s| s.bold()),
+                help
+            ));
+        }
+
+        output
+    }
+
+    /// Renders the diagnostic as a single line of JSON:
+    ///
+    /// ```text
+    /// {"severity":"error","code":"E0302","message":"Operands must be numbers.","file":"script.lux","line":2,"column":7,"span":{"start":17,"end":24},"notes":[],"help":null,"trace":[]}
+    /// ```
+    ///
+    /// Spans are byte offsets into the file, and columns count characters
+    /// from 1.
+    pub fn to_json(&self, script: &Path, source: &str) -> String {
+        let file = self.file.as_deref().unwrap_or(script);
+        let text = self.source(script, source);
+        let (line, column) = text
+            .as_deref()
+            .and_then(|t| self.snippet(t))
+            .map_or((self.line, self.column), |s| (s.line_number, s.column));
+
+        let strings = |items: &[String]| {
+            let items: Vec<String> = items.iter().map(|item| json_string(item)).collect();
+            format!("[{}]", items.join(","))
+        };
+        let trace: Vec<String> = self
+            .trace
+            .iter()
+            .map(|line| {
+                format!(
+                    "{{\"function\":{},\"file\":{},\"line\":{}}}",
+                    json_string(&line.function),
+                    json_string(&line.file.display().to_string()),
+                    line.line
+                )
+            })
+            .collect();
+
+        format!(
+            "{{\"severity\":{},\"code\":{},\"message\":{},\"file\":{},\"line\":{},\"column\":{},\"span\":{{\"start\":{},\"end\":{}}},\"notes\":{},\"help\":{},\"trace\":[{}]}}",
+            json_string(self.severity.name()),
+            json_string(self.code.as_str()),
+            json_string(&self.message),
+            json_string(&file.display().to_string()),
+            line,
+            column,
+            self.span.start,
+            self.span.end,
+            strings(&self.notes),
+            self.help.as_deref().map_or("null".to_string(), json_string),
+            trace.join(",")
+        )
+    }
+
+    // The text of the file the diagnostic points into.
+    fn source(&self, script: &Path, source: &str) -> Option<String> {
+        match self.file.as_deref() {
+            Some(file) if file != script => fs::read_to_string(file).ok(),
+            _ => Some(source.to_string()),
+        }
+    }
+
+    // One `at function (line N)` line per call, naming the file for calls
+    // outside the script being run. Runs of the same line, as in deep
+    // recursion, are shown once with a count.
+    fn render_trace(&self, script: &Path, gutter: &str) -> String {
+        let mut output = String::new();
+        let mut lines = self.trace.iter().peekable();
+        while let Some(line) = lines.next() {
+            let mut repeats = 0;
+            while lines.peek() == Some(&line) {
+                lines.next();
+                repeats += 1;
+            }
+
+            let location = if *line.file == *script {
+                format!("line {}", line.line)
+            } else {
+                format!("{}, line {}", line.file.display(), line.line)
+            };
+            output.push_str(&format!(
+                "{}     at {} ({})\n",
+                gutter, line.function, location
+            ));
+            if repeats > 0 {
+                output.push_str(&format!(
+                    "{}     ... the line above repeated {} more times\n",
+                    gutter, repeats
+                ));
+            }
+        }
+        output
+    }
+
+    // Spans running over several lines are underlined to the end of the
+    // first.
+    fn snippet<'a>(&self, source: &'a str) -> Option<Snippet<'a>> {
+        if self.span.start > source.len() || !source.is_char_boundary(self.span.start) {
+            return None;
+        }
+
+        let line_start = source[..self.span.start]
+            .rfind('\n')
+            .map_or(0, |newline| newline + 1);
+        let line_end = source[self.span.start..]
+            .find('\n')
+            .map_or(source.len(), |newline| self.span.start + newline);
+
+        // Tabs are kept so the caret lines up however wide the terminal
+        // draws them.
+        let padding: String = source[line_start..self.span.start]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let end = self.span.end.clamp(self.span.start, line_end);
+        let width = source
+            .get(self.span.start..end)
+            .map_or(0, |text| text.chars().count())
+            .max(1);
+
+        Some(Snippet {
+            line: source[line_start..line_end].trim_end_matches('\r'),
+            line_number: source[..line_start].matches('\n').count() + 1,
+            column: padding.chars().count() + 1,
+            padding,
+            width,
+        })
+    }
+}
+
+// The source line a span starts on, and where on it the span is.
+struct Snippet<'a> {
+    line: &'a str,
+    line_number: usize,
+    column: usize,
+    // Whitespace lining a caret up with the start of the span.
+    padding: String,
+    // How many carets underline the span.
+    width: usize,
+}
+
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+struct Style {
+    color: bool,
+}
+
+impl Style {
+    fn paint(&self, text: &str, colorize: fn(&str) -> ColoredString) -> String {
+        if self.color {
+            colorize(text).to_string()
+        } else {
+            text.to_string()
+        }
+    }
+}
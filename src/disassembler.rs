@@ -0,0 +1,120 @@
+use std::fmt::Write;
+
+use crate::{
+    chunk::{Chunk, OpCode},
+    literal::Literal,
+    object::FunctionProto,
+};
+
+/// Lists the instructions of `function` and then, in the order they are
+/// declared, of every function inside it. Each line holds the instruction's
+/// offset, its line (or `|` when it is the same as the previous one), the
+/// opcode and what its operands refer to.
+pub fn disassemble(function: &FunctionProto) -> String {
+    let mut out = String::new();
+    disassemble_function(function, &mut out);
+    out
+}
+
+fn disassemble_function(function: &FunctionProto, out: &mut String) {
+    writeln!(out, "== {} ==", function.to_str()).unwrap();
+    let chunk = &function.chunk;
+    let mut offset = 0;
+    let mut previous_line = None;
+    while offset < chunk.code.len() {
+        let line = chunk.line(offset);
+        if previous_line == Some(line) {
+            write!(out, "{:04}    | ", offset).unwrap();
+        } else {
+            write!(out, "{:04} {:>4} ", offset, line).unwrap();
+        }
+        previous_line = Some(line);
+        offset = instruction(chunk, offset, out);
+    }
+
+    for inner in &chunk.functions {
+        writeln!(out).unwrap();
+        disassemble_function(inner, out);
+    }
+}
+
+// Writes the instruction at `offset` and returns the offset of the next one.
+fn instruction(chunk: &Chunk, offset: usize, out: &mut String) -> usize {
+    let op = OpCode::from_byte(chunk.code[offset]);
+    let name = format!("{:?}", op);
+    let operand = || usize::from(chunk.read_u16(offset + 1));
+    match op {
+        OpCode::Constant
+        | OpCode::GetGlobal
+        | OpCode::DefineGlobal
+        | OpCode::SetGlobal
+        | OpCode::GetProperty
+        | OpCode::SetProperty
+        | OpCode::GetSuper
+        | OpCode::Class
+        | OpCode::Method
+        | OpCode::Import
+        | OpCode::ImportName => {
+            let index = operand();
+            let constant = constant(&chunk.constants[index]);
+            writeln!(out, "{:<16} {:4} {}", name, index, constant).unwrap();
+            offset + 3
+        }
+        OpCode::GetLocal | OpCode::SetLocal => {
+            writeln!(out, "{:<16} {:4} slot", name, operand()).unwrap();
+            offset + 3
+        }
+        OpCode::GetUpvalue | OpCode::SetUpvalue => {
+            writeln!(out, "{:<16} {:4} upvalue", name, operand()).unwrap();
+            offset + 3
+        }
+        OpCode::Jump | OpCode::JumpIfFalse | OpCode::PushCatch | OpCode::PushFinally => {
+            let jump = chunk.read_u32(offset + 1) as usize;
+            let target = offset + 5 + jump;
+            writeln!(out, "{:<16} {:4} -> {:04}", name, jump, target).unwrap();
+            offset + 5
+        }
+        OpCode::Loop => {
+            let jump = chunk.read_u32(offset + 1) as usize;
+            let target = offset + 5 - jump;
+            writeln!(out, "{:<16} {:4} -> {:04}", name, jump, target).unwrap();
+            offset + 5
+        }
+        OpCode::Call => {
+            let count = chunk.code[offset + 1];
+            writeln!(out, "{:<16} {:4} args", name, count).unwrap();
+            offset + 2
+        }
+        OpCode::List | OpCode::Map | OpCode::Interpolate => {
+            writeln!(out, "{:<16} {:4} items", name, operand()).unwrap();
+            offset + 3
+        }
+        OpCode::Closure => {
+            let index = operand();
+            let function = &chunk.functions[index];
+            writeln!(out, "{:<16} {:4} {}", name, index, function.to_str()).unwrap();
+            let mut offset = offset + 3;
+            for _ in 0..function.upvalues {
+                let kind = match chunk.code[offset] {
+                    1 => "local",
+                    _ => "upvalue",
+                };
+                let captured = chunk.read_u16(offset + 1);
+                writeln!(out, "{:04}    |   captures {} {}", offset, kind, captured).unwrap();
+                offset += 3;
+            }
+            offset
+        }
+        _ => {
+            writeln!(out, "{}", name).unwrap();
+            offset + 1
+        }
+    }
+}
+
+fn constant(value: &Literal) -> String {
+    match value {
+        Literal::String(string) => format!("{:?}", string),
+        _ => value.to_string(),
+    }
+}
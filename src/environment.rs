@@ -1,96 +1,128 @@
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 use crate::{
-    interpreter::RuntimeResult, literal::Literal, runtime_error::RuntimeError, token::Token,
+    error_code::ErrorCode, interpreter::RuntimeResult, literal::Literal,
+    runtime_error::RuntimeError, suggestion::did_you_mean, token::Token,
 };
 
+/// Where the resolver found a local variable: how many environments out from
+/// the one in use it lives, and its slot there.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Slot {
+    pub depth: usize,
+    pub index: usize,
+}
+
 #[derive(PartialEq, Debug, Clone, Eq)]
 pub struct Environment {
-    values: HashMap<String, Literal>,
+    /// Local variables, in the order they were declared, which is the order
+    /// the resolver numbered their slots in.
+    slots: Vec<Literal>,
+    /// Global variables by name. Only the outermost environment of a chain
+    /// has any: globals can be defined after the code using them is resolved,
+    /// so they don't get slots.
+    globals: HashMap<String, Literal>,
     enclosing: Option<Rc<RefCell<Environment>>>,
 }
 
 impl Environment {
     pub fn new() -> Rc<RefCell<Self>> {
         Rc::new(RefCell::new(Self {
-            values: HashMap::new(),
+            slots: Vec::new(),
+            globals: HashMap::new(),
             enclosing: None,
         }))
     }
     pub fn new_with(enclosing: Rc<RefCell<Self>>) -> Rc<RefCell<Self>> {
         Rc::new(RefCell::new(Self {
-            values: HashMap::new(),
+            slots: Vec::new(),
+            globals: HashMap::new(),
             enclosing: Some(enclosing),
         }))
     }
 
+    /// The outermost environment of the chain `environment` belongs to, which
+    /// holds the globals of the module the running code was defined in.
+    pub fn global(environment: &Rc<RefCell<Self>>) -> Rc<RefCell<Self>> {
+        let mut env = environment.clone();
+        loop {
+            let enclosing = env.borrow().enclosing.clone();
+            match enclosing {
+                Some(enclosing) => env = enclosing,
+                None => return env,
+            }
+        }
+    }
+
+    /// Defines a global by name, or a local in the next slot.
     pub fn define(&mut self, name: String, value: Literal) {
-        self.values.insert(name, value);
+        if self.enclosing.is_none() {
+            self.globals.insert(name, value);
+        } else {
+            self.slots.push(value);
+        }
     }
 
+    /// Looks up a global.
     pub fn get(&self, name: &Token) -> RuntimeResult<Literal> {
-        let key = name.lexeme.as_str();
-
-        if let Some(val) = self.values.get(key) {
-            return Ok(val.clone());
-        } else if let Some(enclosing) = &self.enclosing {
-            let env = enclosing.try_borrow().unwrap();
-            return env.get(name);
+        match self.lookup(&name.lexeme) {
+            Some(value) => Ok(value),
+            None => Err(RuntimeError::new(
+                name.to_owned(),
+                ErrorCode::UndefinedVariable,
+                format!("Undefined variable '{}'.", name.lexeme.to_owned()),
+            )),
         }
-
-        Err(RuntimeError::new(
-            name.to_owned(),
-            format!("Undefined variable '{}'.", name.lexeme.to_owned()),
-        ))
     }
 
-    pub fn assign_at(
-        &mut self,
-        distance: usize,
-        token: Token,
-        value: Literal,
-    ) -> RuntimeResult<()> {
-        self.ancestor(distance)
-            .try_borrow_mut()
-            .unwrap()
-            .values
-            .insert(token.lexeme, value)
-            .unwrap();
+    pub fn lookup(&self, name: &str) -> Option<Literal> {
+        self.globals.get(name).cloned()
+    }
 
-        Ok(())
+    /// A hint naming the global that `name` is closest to, if any is close
+    /// enough to be a typo.
+    pub fn suggest(&self, name: &str) -> Option<String> {
+        did_you_mean(name, self.globals.keys().map(String::as_str))
     }
 
+    /// Assigns to an existing global.
     pub fn assign(&mut self, token: Token, value: Literal) -> RuntimeResult<()> {
-        if let std::collections::hash_map::Entry::Occupied(mut e) =
-            self.values.entry(token.lexeme.clone())
-        {
-            e.insert(value);
-            return Ok(());
-        } else if let Some(enclosing) = &mut self.enclosing {
-            return enclosing.borrow_mut().assign(token, value);
+        if self.update(&token.lexeme, value) {
+            Ok(())
+        } else {
+            Err(RuntimeError::new(
+                token.clone(),
+                ErrorCode::UndefinedVariable,
+                format!("Undefined variable '{}'.", token.lexeme),
+            ))
         }
-
-        Err(RuntimeError::new(
-            token.clone(),
-            format!("Undefined variable '{}'.", token.lexeme),
-        ))
     }
 
-    pub fn get_at(&self, distance: usize, name: &str) -> RuntimeResult<Literal> {
-        let ancestor = self.ancestor(distance);
-        let borrowed_ancestor = ancestor.try_borrow().unwrap();
-        let value = borrowed_ancestor.values.get(name).unwrap();
+    /// Assigns to an existing global, returning whether there was one.
+    pub fn update(&mut self, name: &str, value: Literal) -> bool {
+        match self.globals.get_mut(name) {
+            Some(slot) => {
+                *slot = value;
+                true
+            }
+            None => false,
+        }
+    }
 
-        Ok(value.clone())
+    pub fn get_at(environment: &Rc<RefCell<Self>>, slot: Slot) -> Literal {
+        Self::ancestor(environment, slot.depth).borrow().slots[slot.index].clone()
     }
 
-    fn ancestor(&self, distance: usize) -> Rc<RefCell<Environment>> {
-        let mut env = Rc::new(RefCell::new(self.clone()));
+    pub fn assign_at(environment: &Rc<RefCell<Self>>, slot: Slot, value: Literal) {
+        Self::ancestor(environment, slot.depth).borrow_mut().slots[slot.index] = value;
+    }
 
+    fn ancestor(environment: &Rc<RefCell<Self>>, distance: usize) -> Rc<RefCell<Self>> {
+        let mut env = environment.clone();
         for _ in 0..distance {
-            env = self.enclosing.clone().unwrap()
+            let enclosing = env.borrow().enclosing.clone().unwrap();
+            env = enclosing;
         }
-
         env
     }
 }
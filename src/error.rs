@@ -1,12 +1,20 @@
-use std::fmt;
+use std::{fmt, path::Path, rc::Rc};
 
-use crate::{token::Token, token_type::Types};
+use crate::{error_code::ErrorCode, span::Span, token::Token, token_type::Types};
 
 #[derive(Clone, Debug)]
 pub struct LuxError {
+    pub code: ErrorCode,
     pub message: String,
     pub line: usize,
-    pub location: String,
+    pub column: usize,
+    pub span: Span,
+    // A boxed `str` rather than a `String`, to keep results with a `LuxError`
+    // small.
+    pub location: Box<str>,
+    /// The file the error is in, when it isn't the script being run.
+    pub file: Option<Rc<Path>>,
+    pub notes: Vec<String>,
 }
 
 // Errors should be printable.
@@ -14,8 +22,8 @@ impl fmt::Display for LuxError {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         write!(
             f,
-            "line {} Error {}: {}",
-            self.line, self.location, self.message
+            "line {}:{} Error {}: {}",
+            self.line, self.column, self.location, self.message
         )
     }
 }
@@ -23,19 +31,30 @@ impl fmt::Display for LuxError {
 impl std::error::Error for LuxError {}
 
 impl LuxError {
-    fn report(line: usize, location: &str, message: &str) -> LuxError {
+    fn report(token: &Token, location: &str, code: ErrorCode, message: &str) -> LuxError {
         LuxError {
-            line,
-            location: location.to_string(),
+            code,
+            line: token.line,
+            column: token.column,
+            span: token.span,
+            location: location.into(),
             message: message.to_string(),
+            file: None,
+            notes: vec![],
         }
     }
 
-    pub fn new(token: &Token, message: &str) -> LuxError {
+    pub fn new(token: &Token, code: ErrorCode, message: &str) -> LuxError {
         if token.type_t == Types::EOF {
-            Self::report(token.line, "at end", message)
+            Self::report(token, "at end", code, message)
         } else {
-            Self::report(token.line, &format!("at '{}'", &token.lexeme), message)
+            Self::report(token, &format!("at '{}'", &token.lexeme), code, message)
         }
     }
+
+    /// Points the error at `span` rather than its token.
+    pub fn with_span(mut self, span: Span) -> LuxError {
+        self.span = span;
+        self
+    }
 }
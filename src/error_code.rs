@@ -0,0 +1,104 @@
+//! Stable codes for every kind of error, so tools reading diagnostics don't
+//! have to match on message text. Codes are never reused or renumbered:
+//!
+//! | Code  | Kind                                              |
+//! |-------|---------------------------------------------------|
+//! | E0001 | Unexpected character                              |
+//! | E0002 | Unterminated string                               |
+//! | E0003 | Invalid escape sequence                           |
+//! | E0100 | Missing or unexpected token                       |
+//! | E0101 | Expected an expression                            |
+//! | E0102 | Invalid assignment target                         |
+//! | E0103 | Too many parameters, arguments or other items for the compiler |
+//! | E0200 | Variable already declared in this scope           |
+//! | E0201 | Local variable read in its own initializer        |
+//! | E0202 | `return` outside a function, or with a value in an initializer |
+//! | E0203 | `this` or `super` used where there is no class or superclass |
+//! | E0204 | A class inheriting from itself                    |
+//! | E0205 | `break` or `continue` outside a loop              |
+//! | E0206 | `import` outside the top level                    |
+//! | E0207 | Module can't be found or read                     |
+//! | E0208 | Import cycle                                      |
+//! | E0300 | Runtime error of no more specific kind            |
+//! | E0301 | Undefined variable                                |
+//! | E0302 | Operation applied to a value of the wrong type    |
+//! | E0303 | Wrong number of arguments                         |
+//! | E0304 | Undefined property, module member or map key      |
+//! | E0305 | List index out of range or not an integer         |
+//! | E0306 | Stack overflow                                    |
+//! | E0307 | Uncaught thrown value                             |
+//! | W0001 | Unused local variable                             |
+//! | W0002 | Unused parameter                                  |
+//! | W0003 | Function never called                             |
+//! | W0004 | Local variable shadowing another                  |
+//! | W0005 | Unreachable code                                  |
+
+/// The kind of an error or warning, set where it is raised.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCode {
+    UnexpectedCharacter,
+    UnterminatedString,
+    InvalidEscape,
+    UnexpectedToken,
+    ExpectedExpression,
+    InvalidAssignmentTarget,
+    TooMany,
+    AlreadyDeclared,
+    ReadInOwnInitializer,
+    InvalidReturn,
+    NoClass,
+    InheritsFromItself,
+    OutsideLoop,
+    ImportOutsideTopLevel,
+    ModuleNotFound,
+    ImportCycle,
+    Runtime,
+    UndefinedVariable,
+    WrongType,
+    WrongArity,
+    UndefinedMember,
+    InvalidIndex,
+    StackOverflow,
+    Uncaught,
+    UnusedVariable,
+    UnusedParameter,
+    UnusedFunction,
+    Shadowing,
+    Unreachable,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::UnexpectedCharacter => "E0001",
+            ErrorCode::UnterminatedString => "E0002",
+            ErrorCode::InvalidEscape => "E0003",
+            ErrorCode::UnexpectedToken => "E0100",
+            ErrorCode::ExpectedExpression => "E0101",
+            ErrorCode::InvalidAssignmentTarget => "E0102",
+            ErrorCode::TooMany => "E0103",
+            ErrorCode::AlreadyDeclared => "E0200",
+            ErrorCode::ReadInOwnInitializer => "E0201",
+            ErrorCode::InvalidReturn => "E0202",
+            ErrorCode::NoClass => "E0203",
+            ErrorCode::InheritsFromItself => "E0204",
+            ErrorCode::OutsideLoop => "E0205",
+            ErrorCode::ImportOutsideTopLevel => "E0206",
+            ErrorCode::ModuleNotFound => "E0207",
+            ErrorCode::ImportCycle => "E0208",
+            ErrorCode::Runtime => "E0300",
+            ErrorCode::UndefinedVariable => "E0301",
+            ErrorCode::WrongType => "E0302",
+            ErrorCode::WrongArity => "E0303",
+            ErrorCode::UndefinedMember => "E0304",
+            ErrorCode::InvalidIndex => "E0305",
+            ErrorCode::StackOverflow => "E0306",
+            ErrorCode::Uncaught => "E0307",
+            ErrorCode::UnusedVariable => "W0001",
+            ErrorCode::UnusedParameter => "W0002",
+            ErrorCode::UnusedFunction => "W0003",
+            ErrorCode::Shadowing => "W0004",
+            ErrorCode::Unreachable => "W0005",
+        }
+    }
+}
@@ -1,38 +1,153 @@
-use crate::{literal::Literal, token::Token};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{function::Function, literal::Literal, span::Span, token::Token};
+
+/// Identifies an expression node, so the resolver's side tables can be keyed
+/// by node rather than by its contents. Ids are unique across every parser in
+/// the process, so nodes from different modules never share one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ExprId(usize);
+
+impl ExprId {
+    pub fn fresh() -> ExprId {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        ExprId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
 
 #[derive(Eq, PartialEq, Clone, Debug, Hash)]
 pub enum Expr {
     Binary {
+        id: ExprId,
         left: Box<Expr>,
         right: Box<Expr>,
         operator: Token,
     },
     Grouping {
+        id: ExprId,
         expression: Box<Expr>,
+        span: Span,
     },
     Literal {
+        id: ExprId,
         value: Literal,
+        span: Span,
     },
     Unary {
+        id: ExprId,
         operator: Token,
         right: Box<Expr>,
     },
     Variable {
+        id: ExprId,
         name: Token,
     },
     Assign {
+        id: ExprId,
         name: Token,
         value: Box<Expr>,
     },
     Logical {
+        id: ExprId,
         left: Box<Expr>,
         operator: Token,
         right: Box<Expr>,
     },
     Call {
+        id: ExprId,
         callee: Box<Expr>,
         paren: Token,
         arguments: Vec<Expr>,
     },
+    Get {
+        id: ExprId,
+        object: Box<Expr>,
+        name: Token,
+    },
+    Set {
+        id: ExprId,
+        object: Box<Expr>,
+        name: Token,
+        value: Box<Expr>,
+    },
+    This {
+        id: ExprId,
+        keyword: Token,
+    },
+    Super {
+        id: ExprId,
+        keyword: Token,
+        method: Token,
+    },
+    List {
+        id: ExprId,
+        elements: Vec<Expr>,
+        span: Span,
+    },
+    Map {
+        id: ExprId,
+        brace: Token,
+        entries: Vec<(Expr, Expr)>,
+        span: Span,
+    },
+    Index {
+        id: ExprId,
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>,
+    },
+    SetIndex {
+        id: ExprId,
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>,
+        value: Box<Expr>,
+    },
+    Lambda {
+        id: ExprId,
+        function: Function,
+    },
+    Interpolation {
+        id: ExprId,
+        parts: Vec<Expr>,
+        span: Span,
+    },
     Nil,
 }
+
+impl Expr {
+    /// The source range the expression was parsed from. Expressions that
+    /// don't hold both their first and last token record it when parsed.
+    /// `Expr::Nil` stands in for a missing initializer or return value, and
+    /// has no source.
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Binary { left, right, .. } | Expr::Logical { left, right, .. } => {
+                left.span().to(right.span())
+            }
+            Expr::Grouping { span, .. }
+            | Expr::Literal { span, .. }
+            | Expr::List { span, .. }
+            | Expr::Map { span, .. }
+            | Expr::Interpolation { span, .. } => *span,
+            Expr::Unary {
+                operator, right, ..
+            } => operator.span.to(right.span()),
+            Expr::Variable { name, .. } => name.span,
+            Expr::Assign { name, value, .. } => name.span.to(value.span()),
+            Expr::Call { callee, paren, .. } => callee.span().to(paren.span),
+            Expr::Get { object, name, .. } => object.span().to(name.span),
+            Expr::Set { object, value, .. } => object.span().to(value.span()),
+            Expr::This { keyword, .. } => keyword.span,
+            Expr::Super {
+                keyword, method, ..
+            } => keyword.span.to(method.span),
+            Expr::Index {
+                object, bracket, ..
+            } => object.span().to(bracket.span),
+            Expr::SetIndex { object, value, .. } => object.span().to(value.span()),
+            Expr::Lambda { function, .. } => function.span,
+            Expr::Nil => Span::default(),
+        }
+    }
+}
@@ -1,25 +1,37 @@
 use std::{
     cell::RefCell,
     hash::{Hash, Hasher},
-    panic::{self, AssertUnwindSafe},
+    path::Path,
     rc::Rc,
 };
 
 use crate::{
     callable::LuxCallable,
-    environment::Environment,
-    interpreter::{Interpreter, RuntimeResult},
+    environment::{Environment, Slot},
+    interpreter::{CallFrame, ControlFlow, Interpreter, RuntimeResult},
     literal::Literal,
+    span::Span,
     stmt::Stmt,
     token::Token,
 };
 use rand::Rng;
 
-#[derive(PartialEq, Clone, Debug, Eq)]
+#[derive(PartialEq, Clone, Debug, Eq, Hash)]
 pub struct Function {
-    pub name: Token,
+    /// `None` for lambdas.
+    pub name: Option<Token>,
     pub param: Vec<Token>,
     pub body: Vec<Stmt>,
+    pub span: Span,
+}
+
+impl Function {
+    /// The name of a function declaration or method, which only lambdas lack.
+    pub fn declared_name(&self) -> &Token {
+        self.name
+            .as_ref()
+            .expect("Only declared functions are looked up by name")
+    }
 }
 
 #[derive(Clone, Debug, Eq)]
@@ -27,6 +39,9 @@ pub struct LuxFunction {
     decleration: Function,
     id: usize,
     closure: Rc<RefCell<Environment>>,
+    is_initializer: bool,
+    // The file the function was declared in, for errors raised in its body.
+    file: Rc<Path>,
 }
 
 impl LuxCallable for LuxFunction {
@@ -34,6 +49,7 @@ impl LuxCallable for LuxFunction {
         &self,
         interpreter: &mut Interpreter,
         arguments: Vec<Literal>,
+        paren: &Token,
     ) -> RuntimeResult<Literal> {
         let environment = Environment::new_with(self.closure.clone());
         for i in 0..self.decleration.param.len() {
@@ -44,30 +60,35 @@ impl LuxCallable for LuxFunction {
                 .define(name.lexeme.clone(), value.clone())
         }
 
-        let hook = panic::take_hook();
-
-        panic::set_hook(Box::new(|_info| {
-            // do nothing
-        }));
-
-        let result = panic::catch_unwind(AssertUnwindSafe(|| {
-            interpreter.execute_block(&self.decleration.body, environment)
-        }));
-
-        panic::set_hook(hook);
-
-        if let Err(return_value) = result {
-            let casted_val = return_value.downcast::<Literal>();
-            if let Ok(val) = casted_val {
-                return Ok(*val);
-            }
+        let frame = CallFrame {
+            function: self.name(),
+            file: self.file.clone(),
+            line: paren.line,
+        };
+        interpreter.push_frame(frame, paren)?;
+        let result = interpreter
+            .execute_block(&self.decleration.body, environment)
+            .map_err(|err| interpreter.capture_trace(err.in_file(&self.file)));
+        interpreter.pop_frame();
+        let flow = result?;
+
+        // `this` is the only variable of a bound method's closure.
+        if self.is_initializer {
+            let this = Slot { depth: 0, index: 0 };
+            return Ok(Environment::get_at(&self.closure, this));
         }
 
-        Ok(Literal::Nil)
+        match flow {
+            ControlFlow::Return(value) => Ok(value),
+            _ => Ok(Literal::Nil),
+        }
     }
 
     fn to_str(&self) -> String {
-        format!("<fn {}>", self.decleration.name.lexeme)
+        match &self.decleration.name {
+            Some(name) => format!("<fn {}>", name.lexeme),
+            None => self.name(),
+        }
     }
 
     fn arity(&self) -> usize {
@@ -80,15 +101,42 @@ impl LuxCallable for LuxFunction {
 }
 
 impl LuxFunction {
-    pub fn new(decleration: Function, closure: Rc<RefCell<Environment>>) -> Self {
+    pub fn new(
+        decleration: Function,
+        closure: Rc<RefCell<Environment>>,
+        is_initializer: bool,
+        file: Rc<Path>,
+    ) -> Self {
         let mut rng = rand::thread_rng();
         let id: usize = rng.gen();
         Self {
             decleration,
             id,
             closure,
+            is_initializer,
+            file,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match &self.decleration.name {
+            Some(name) => name.lexeme.clone(),
+            None => "<lambda>".to_string(),
         }
     }
+
+    pub fn bind(&self, instance: Literal) -> LuxFunction {
+        let environment = Environment::new_with(self.closure.clone());
+        environment
+            .borrow_mut()
+            .define("this".to_string(), instance);
+        LuxFunction::new(
+            self.decleration.clone(),
+            environment,
+            self.is_initializer,
+            self.file.clone(),
+        )
+    }
 }
 
 impl PartialEq for LuxFunction {
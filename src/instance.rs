@@ -0,0 +1,68 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
+
+use crate::{
+    class::LuxClass, error_code::ErrorCode, interpreter::RuntimeResult, literal::Literal,
+    runtime_error::RuntimeError, token::Token,
+};
+use rand::Rng;
+
+#[derive(Clone, Debug, Eq)]
+pub struct LuxInstance {
+    class: LuxClass,
+    fields: Rc<RefCell<HashMap<String, Literal>>>,
+    id: usize,
+}
+
+impl LuxInstance {
+    pub fn new(class: LuxClass) -> Self {
+        let mut rng = rand::thread_rng();
+        let id: usize = rng.gen();
+        Self {
+            class,
+            fields: Rc::new(RefCell::new(HashMap::new())),
+            id,
+        }
+    }
+
+    pub fn get(&self, name: &Token) -> RuntimeResult<Literal> {
+        if let Some(value) = self.fields.borrow().get(&name.lexeme) {
+            return Ok(value.clone());
+        }
+
+        if let Some(method) = self.class.find_method(&name.lexeme) {
+            let bound = method.bind(Literal::Instance(Box::new(self.clone())));
+            return Ok(Literal::Function(Box::new(bound)));
+        }
+
+        Err(RuntimeError::new(
+            name.to_owned(),
+            ErrorCode::UndefinedMember,
+            format!("Undefined property '{}'.", name.lexeme),
+        ))
+    }
+
+    pub fn set(&self, name: &Token, value: Literal) {
+        self.fields.borrow_mut().insert(name.lexeme.clone(), value);
+    }
+
+    pub fn to_str(&self) -> String {
+        format!("{} instance", self.class.name)
+    }
+}
+
+impl PartialEq for LuxInstance {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Hash for LuxInstance {
+    fn hash<H: Hasher>(&self, hasher: &mut H) {
+        self.id.hash(hasher);
+    }
+}
@@ -1,51 +1,107 @@
 use std::collections::HashMap;
-use std::panic::{panic_any, UnwindSafe};
+use std::path::{Path, PathBuf};
 use std::{cell::RefCell, ops::Neg, rc::Rc};
 
 use crate::callable::LuxCallable;
+use crate::class::LuxClass;
 use crate::function::LuxFunction;
+use crate::list::LuxList;
 use crate::literal::Literal;
+use crate::map::LuxMap;
+use crate::module::ModuleLoader;
+use crate::native::NativeFunction;
 use crate::stmt::Stmt;
 use crate::token::Token;
 use crate::{
-    clock::Clock, environment::Environment, expr::Expr, runtime_error::RuntimeError,
+    clock::Clock,
+    environment::{Environment, Slot},
+    error_code::ErrorCode,
+    expr::{Expr, ExprId},
+    runtime_error::{RuntimeError, TraceLine},
     token_type::Types,
 };
 pub type RuntimeResult<T> = Result<T, RuntimeError>;
 
+/// Calls nested deeper than this raise a "Stack overflow." error instead of
+/// overflowing the interpreter's own stack.
+pub const MAX_CALL_DEPTH: usize = 5000;
+
+/// A call to a Lux function that hasn't returned yet.
+#[derive(Clone, Debug)]
+pub struct CallFrame {
+    pub function: String,
+    /// The file the function is declared in.
+    pub file: Rc<Path>,
+    /// The line the function was called from.
+    pub line: usize,
+}
+
+/// How a statement finished executing. Anything other than `Normal` is
+/// passed up until it reaches the loop or function call that handles it.
+#[derive(Debug, PartialEq)]
+pub enum ControlFlow {
+    Normal,
+    Return(Literal),
+    Break,
+    Continue,
+}
+
 pub struct Interpreter {
     pub environment: Rc<RefCell<Environment>>,
     pub globals: Rc<RefCell<Environment>>,
-    pub locals: HashMap<Expr, usize>,
+    pub locals: HashMap<ExprId, Slot>,
+    hints: HashMap<ExprId, String>,
+    pub modules: ModuleLoader,
+    /// Module paths in `import` statements are relative to this directory.
+    pub directory: PathBuf,
+    /// The file whose code is being resolved or run.
+    pub file: Rc<Path>,
+    pub call_stack: Vec<CallFrame>,
 }
 
-impl UnwindSafe for Interpreter {}
-
 impl Interpreter {
     pub fn new() -> Self {
-        let globals = Environment::new();
+        let globals = Self::new_globals();
         let environment = globals.clone();
-        let locals: HashMap<Expr, usize> = HashMap::new();
-        globals
-            .borrow_mut()
-            .define("clock".to_string(), Literal::Clock(Clock::new()));
 
         Self {
             environment,
             globals,
-            locals,
+            locals: HashMap::new(),
+            hints: HashMap::new(),
+            modules: ModuleLoader::default(),
+            directory: PathBuf::from("."),
+            file: Rc::from(Path::new("<stdin>")),
+            call_stack: Vec::new(),
         }
     }
+
+    /// A global environment holding the native functions.
+    pub fn new_globals() -> Rc<RefCell<Environment>> {
+        let globals = Environment::new();
+        globals
+            .borrow_mut()
+            .define("clock".to_string(), Literal::Clock(Clock::new()));
+        for native in NativeFunction::globals() {
+            globals
+                .borrow_mut()
+                .define(native.name.to_string(), Literal::Native(Box::new(native)));
+        }
+        globals
+    }
+
     pub fn evaluate(&mut self, expr: &Expr) -> RuntimeResult<Literal> {
         match expr {
             Expr::Binary {
                 left,
                 right,
                 operator,
+                ..
             } => {
                 let eval_left = self.evaluate(left)?;
                 let eval_right = self.evaluate(right)?;
-                let num_err = Self::check_number_operands(operator, &eval_left, &eval_right);
+                let num_err = Self::check_number_operands(operator, &eval_left, &eval_right)
+                    .map_err(|err| err.with_span(expr.span()));
 
                 match operator.type_t {
                     Types::MINUS => {
@@ -85,8 +141,10 @@ impl Interpreter {
                         }
                         Err(RuntimeError::new(
                             operator.to_owned(),
+                            ErrorCode::WrongType,
                             "Operands must be two numbers or two strings.".to_string(),
-                        ))
+                        )
+                        .with_span(expr.span()))
                     }
                     Types::GREATER => {
                         if let Literal::Number(num_left) = eval_left {
@@ -135,67 +193,59 @@ impl Interpreter {
 
                     _ => Err(RuntimeError::new(
                         operator.to_owned(),
+                        ErrorCode::WrongType,
                         "Operator type not implemented!".to_string(),
                     )),
                 }
             }
-            Expr::Grouping { expression } => self.evaluate(expression),
-            Expr::Literal { value } => Ok(value.to_owned()),
-            Expr::Unary { operator, right } => {
+            Expr::Grouping { expression, .. } => self.evaluate(expression),
+            Expr::Literal { value, .. } => Ok(value.to_owned()),
+            Expr::Unary {
+                operator, right, ..
+            } => {
                 let eval_right = self.evaluate(right)?;
 
                 match operator.type_t {
                     Types::MINUS => {
-                        let is_err = Self::check_number_operand(operator, &eval_right);
+                        let is_err = Self::check_number_operand(operator, &eval_right)
+                            .map_err(|err| err.with_span(expr.span()));
                         if let Literal::Number(num) = eval_right {
                             Ok(Literal::Number(num.neg()))
                         } else {
                             is_err
                         }
                     }
-                    Types::BANG => {
-                        if eval_right.is_truthy() {
-                            Ok(Literal::Bool(true))
-                        } else {
-                            Ok(Literal::Bool(false))
-                        }
-                    }
+                    Types::BANG => Ok(Literal::Bool(!eval_right.is_truthy())),
                     _ => Err(RuntimeError::new(
                         operator.to_owned(),
+                        ErrorCode::WrongType,
                         "not implemented".to_string(),
                     )),
                 }
             }
             Expr::Nil => Ok(Literal::Nil),
-            Expr::Variable { name } => {
-                let var = self.lookup_variable(name, expr)?;
+            Expr::Variable { id, name } => {
+                let var = self.lookup_variable(name, *id)?;
                 Ok(var)
             }
-            Expr::Assign { name, value } => {
+            Expr::Assign { id, name, value } => {
                 let eval_val = self.evaluate(value)?;
-                let distance = self.locals.get(expr);
-
-                if let Some(distance) = distance {
-                    self.environment.borrow_mut().assign_at(
-                        distance.to_owned(),
-                        name.clone(),
-                        eval_val.clone(),
-                    )?;
+                if let Some(slot) = self.locals.get(id) {
+                    Environment::assign_at(&self.environment, *slot, eval_val.clone());
                 } else {
-                    self.globals
+                    let assigned = Environment::global(&self.environment)
                         .borrow_mut()
-                        .assign(name.clone(), eval_val.clone())?;
+                        .assign(name.clone(), eval_val.clone());
+                    assigned.map_err(|err| self.with_suggestion(err, name, *id))?;
                 }
 
-                self.environment
-                    .borrow_mut()
-                    .assign(name.clone(), eval_val.clone())?;
                 Ok(eval_val)
             }
             Expr::Logical {
                 left,
                 operator,
                 right,
+                ..
             } => {
                 let eval_left = self.evaluate(left)?;
 
@@ -213,6 +263,7 @@ impl Interpreter {
                 arguments,
                 callee,
                 paren,
+                ..
             } => {
                 let eval_callee = self.evaluate(callee.as_ref())?;
                 let mut eval_arguments: Vec<Literal> = vec![];
@@ -221,24 +272,163 @@ impl Interpreter {
                     eval_arguments.push(self.evaluate(argument)?);
                 }
 
-                match eval_callee {
-                    Literal::Function(func) => {
-                        if eval_arguments.len() != func.arity() {
-                            return Err(RuntimeError {
-                                token: paren.clone(),
-                                message: format!(
-                                    "Expected {} arguments but got {}.",
-                                    func.arity(),
-                                    eval_arguments.len()
-                                ),
-                            });
-                        }
+                let func: Box<dyn LuxCallable> = match eval_callee {
+                    Literal::Function(func) => func,
+                    Literal::Class(class) => class,
+                    Literal::Clock(clock) => Box::new(clock),
+                    Literal::Native(native) => native,
+                    _ => {
+                        return Err(RuntimeError::new(
+                            paren.clone(),
+                            ErrorCode::WrongType,
+                            "Can only call functions and classes.".to_string(),
+                        )
+                        .with_span(callee.span()))
+                    }
+                };
 
-                        Ok(func.call(self, eval_arguments)?)
+                if eval_arguments.len() != func.arity() {
+                    return Err(RuntimeError::new(
+                        paren.clone(),
+                        ErrorCode::WrongArity,
+                        format!(
+                            "Expected {} arguments but got {}.",
+                            func.arity(),
+                            eval_arguments.len()
+                        ),
+                    )
+                    .with_span(expr.span()));
+                }
+
+                Ok(func.call(self, eval_arguments, paren)?)
+            }
+            Expr::Get { object, name, .. } => match self.evaluate(object)? {
+                Literal::Instance(instance) => instance.get(name),
+                Literal::Error(error) => error.get(name),
+                Literal::Module(module) => module.get(name),
+                _ => Err(RuntimeError::new(
+                    name.clone(),
+                    ErrorCode::WrongType,
+                    "Only instances have properties.".to_string(),
+                )),
+            },
+            Expr::Set {
+                object,
+                name,
+                value,
+                ..
+            } => match self.evaluate(object)? {
+                Literal::Instance(instance) => {
+                    let value = self.evaluate(value)?;
+                    instance.set(name, value.clone());
+                    Ok(value)
+                }
+                _ => Err(RuntimeError::new(
+                    name.clone(),
+                    ErrorCode::WrongType,
+                    "Only instances have fields.".to_string(),
+                )),
+            },
+            Expr::This { id, keyword } => self.lookup_variable(keyword, *id),
+            Expr::Interpolation { parts, .. } => {
+                let mut value = String::new();
+                for part in parts {
+                    value.push_str(&self.evaluate(part)?.to_string());
+                }
+                Ok(Literal::String(value))
+            }
+            Expr::List { elements, .. } => {
+                let mut values: Vec<Literal> = Vec::new();
+                for element in elements {
+                    values.push(self.evaluate(element)?);
+                }
+                Ok(Literal::List(LuxList::new(values)))
+            }
+            Expr::Map { brace, entries, .. } => {
+                let map = LuxMap::new();
+                for (key, value) in entries {
+                    let key = self.evaluate(key)?;
+                    let value = self.evaluate(value)?;
+                    map.set(key, value, brace)?;
+                }
+                Ok(Literal::Map(map))
+            }
+            Expr::Index {
+                object,
+                bracket,
+                index,
+                ..
+            } => {
+                let object = self.evaluate(object)?;
+                let index = self.evaluate(index)?;
+                match object {
+                    Literal::List(list) => list.get(&index, bracket),
+                    Literal::Map(map) => map.get(&index, bracket),
+                    _ => Err(RuntimeError::new(
+                        bracket.clone(),
+                        ErrorCode::WrongType,
+                        "Only lists and maps can be indexed.".to_string(),
+                    )),
+                }
+            }
+            Expr::SetIndex {
+                object,
+                bracket,
+                index,
+                value,
+                ..
+            } => {
+                let object = self.evaluate(object)?;
+                let index = self.evaluate(index)?;
+                match object {
+                    Literal::List(list) => {
+                        let value = self.evaluate(value)?;
+                        list.set(&index, value.clone(), bracket)?;
+                        Ok(value)
+                    }
+                    Literal::Map(map) => {
+                        let value = self.evaluate(value)?;
+                        map.set(index, value.clone(), bracket)?;
+                        Ok(value)
                     }
                     _ => Err(RuntimeError::new(
-                        paren.clone(),
-                        "Can only call functions and classes.".to_string(),
+                        bracket.clone(),
+                        ErrorCode::WrongType,
+                        "Only lists and maps can be indexed.".to_string(),
+                    )),
+                }
+            }
+            Expr::Lambda { function, .. } => {
+                let lux_function = LuxFunction::new(
+                    function.clone(),
+                    self.environment.clone(),
+                    false,
+                    self.file.clone(),
+                );
+                Ok(Literal::Function(Box::new(lux_function)))
+            }
+            Expr::Super { id, method, .. } => {
+                // `this` is the only variable in the environment just inside
+                // the one holding `super`.
+                let slot = self.locals[id];
+                let superclass = Environment::get_at(&self.environment, slot);
+                let this = Slot {
+                    depth: slot.depth - 1,
+                    index: 0,
+                };
+                let object = Environment::get_at(&self.environment, this);
+
+                let found = match &superclass {
+                    Literal::Class(class) => class.find_method(&method.lexeme),
+                    _ => None,
+                };
+
+                match found {
+                    Some(found) => Ok(Literal::Function(Box::new(found.bind(object)))),
+                    None => Err(RuntimeError::new(
+                        method.clone(),
+                        ErrorCode::UndefinedMember,
+                        format!("Undefined property '{}'.", method.lexeme),
                     )),
                 }
             }
@@ -251,6 +441,7 @@ impl Interpreter {
         } else {
             Err(RuntimeError::new(
                 operator.to_owned(),
+                ErrorCode::WrongType,
                 "Operand must be a number.".to_string(),
             ))
         }
@@ -263,6 +454,7 @@ impl Interpreter {
     ) -> RuntimeResult<Literal> {
         let err = Err(RuntimeError::new(
             operator.to_owned(),
+            ErrorCode::WrongType,
             "Operands must be numbers.".to_string(),
         ));
         if let Literal::Number(_) = left {
@@ -275,6 +467,46 @@ impl Interpreter {
         }
     }
 
+    pub fn push_frame(&mut self, frame: CallFrame, paren: &Token) -> RuntimeResult<()> {
+        if self.call_stack.len() >= MAX_CALL_DEPTH {
+            return Err(RuntimeError::new(
+                paren.clone(),
+                ErrorCode::StackOverflow,
+                "Stack overflow.".to_string(),
+            ));
+        }
+        self.call_stack.push(frame);
+        Ok(())
+    }
+
+    pub fn pop_frame(&mut self) {
+        self.call_stack.pop();
+    }
+
+    /// Records the calls in progress on an error leaving the innermost of
+    /// them, starting with the line the error was raised on.
+    pub fn capture_trace(&self, mut error: RuntimeError) -> RuntimeError {
+        if !error.trace.is_empty() {
+            return error;
+        }
+
+        let mut line = error.token.line;
+        for frame in self.call_stack.iter().rev() {
+            error.trace.push(TraceLine {
+                function: frame.function.clone(),
+                file: frame.file.clone(),
+                line,
+            });
+            line = frame.line;
+        }
+        error.trace.push(TraceLine {
+            function: "<script>".to_string(),
+            file: self.file.clone(),
+            line,
+        });
+        error
+    }
+
     pub fn interpret(&mut self, statements: &[Stmt]) -> RuntimeResult<()> {
         for statement in statements {
             self.execute(statement)?;
@@ -282,85 +514,235 @@ impl Interpreter {
         Ok(())
     }
 
-    pub fn execute(&mut self, statement: &Stmt) -> RuntimeResult<()> {
+    pub fn execute(&mut self, statement: &Stmt) -> RuntimeResult<ControlFlow> {
         match statement {
-            Stmt::Expression { expression } => {
+            Stmt::Expression { expression, .. } => {
                 self.evaluate(expression)?;
             }
-            Stmt::Print { expression } => {
-                let eval_exp = self.evaluate(expression).unwrap().to_string();
+            Stmt::Print { expression, .. } => {
+                let eval_exp = self.evaluate(expression)?.to_string();
                 println!("{}", eval_exp);
             }
-            Stmt::Var { name, initializer } => {
+            Stmt::Var {
+                name, initializer, ..
+            } => {
                 let value = self.evaluate(initializer)?;
                 self.environment
                     .borrow_mut()
                     .define(name.lexeme.clone(), value)
             }
-            Stmt::Block { statements } => {
+            Stmt::Block { statements, .. } => {
                 let environment = self.environment.clone();
-                self.execute_block(statements, Environment::new_with(environment))?;
+                return self.execute_block(statements, Environment::new_with(environment));
             }
             Stmt::If {
                 condition,
                 then_branch,
                 else_branch,
+                ..
             } => {
                 let eval_cond = self.evaluate(condition)?;
                 if eval_cond.is_truthy() {
-                    self.execute(then_branch)?;
+                    return self.execute(then_branch);
                 } else if let Some(else_branch) = else_branch {
-                    self.execute(else_branch)?
+                    return self.execute(else_branch);
                 }
             }
-            Stmt::While { condition, body } => {
+            Stmt::While {
+                condition,
+                body,
+                increment,
+                ..
+            } => {
                 while self.evaluate(condition)?.is_truthy() {
-                    self.execute(body)?;
+                    match self.execute(body)? {
+                        ControlFlow::Break => break,
+                        ControlFlow::Return(value) => return Ok(ControlFlow::Return(value)),
+                        ControlFlow::Normal | ControlFlow::Continue => {}
+                    }
+
+                    if let Some(increment) = increment {
+                        self.evaluate(increment)?;
+                    }
                 }
             }
+            Stmt::Import {
+                path, alias, names, ..
+            } => {
+                let module = ModuleLoader::import(self, path)?;
+
+                if let Some(alias) = alias {
+                    self.environment.borrow_mut().define(
+                        alias.lexeme.clone(),
+                        Literal::Module(Box::new(module.clone())),
+                    );
+                }
+
+                for name in names {
+                    let value = module.get(name)?;
+                    self.environment
+                        .borrow_mut()
+                        .define(name.lexeme.clone(), value);
+                }
+            }
+            Stmt::Throw { keyword, value, .. } => {
+                let value = self.evaluate(value)?;
+                return Err(RuntimeError::thrown(keyword.clone(), value));
+            }
+            Stmt::Try {
+                body,
+                catch,
+                finally,
+                ..
+            } => {
+                let environment = Environment::new_with(self.environment.clone());
+                let mut result = self.execute_block(body, environment);
+
+                if let Some((name, catch_body)) = catch {
+                    if let Err(error) = result {
+                        let environment = Environment::new_with(self.environment.clone());
+                        environment
+                            .borrow_mut()
+                            .define(name.lexeme.clone(), error.into_value());
+                        result = self.execute_block(catch_body, environment);
+                    }
+                }
+
+                if let Some(finally_body) = finally {
+                    let environment = Environment::new_with(self.environment.clone());
+                    // An error or jump out of `finally` replaces the outcome of
+                    // the try and catch blocks.
+                    let flow = self.execute_block(finally_body, environment)?;
+                    if flow != ControlFlow::Normal {
+                        return Ok(flow);
+                    }
+                }
+
+                return result;
+            }
+            Stmt::Break { .. } => return Ok(ControlFlow::Break),
+            Stmt::Continue { .. } => return Ok(ControlFlow::Continue),
             Stmt::Function(stmt) => {
-                let lux_function = LuxFunction::new(stmt.clone(), self.environment.clone());
+                let lux_function = LuxFunction::new(
+                    stmt.clone(),
+                    self.environment.clone(),
+                    false,
+                    self.file.clone(),
+                );
                 self.environment.borrow_mut().define(
-                    stmt.name.lexeme.clone(),
+                    stmt.declared_name().lexeme.clone(),
                     Literal::Function(Box::new(lux_function)),
                 )
             }
+            Stmt::Class {
+                name,
+                superclass,
+                methods,
+                ..
+            } => {
+                let superclass = match superclass {
+                    Some(superclass) => match self.evaluate(superclass)? {
+                        Literal::Class(class) => Some(*class),
+                        _ => {
+                            let token = match superclass.as_ref() {
+                                Expr::Variable { name, .. } => name.clone(),
+                                _ => name.clone(),
+                            };
+                            return Err(RuntimeError::new(
+                                token,
+                                ErrorCode::WrongType,
+                                "Superclass must be a class.".to_string(),
+                            ));
+                        }
+                    },
+                    None => None,
+                };
+
+                let enclosing = self.environment.clone();
+                if let Some(superclass) = &superclass {
+                    self.environment = Environment::new_with(enclosing.clone());
+                    self.environment.borrow_mut().define(
+                        "super".to_string(),
+                        Literal::Class(Box::new(superclass.clone())),
+                    );
+                }
+
+                let mut class_methods: HashMap<String, LuxFunction> = HashMap::new();
+                for method in methods {
+                    let method_name = &method.declared_name().lexeme;
+                    let function = LuxFunction::new(
+                        method.clone(),
+                        self.environment.clone(),
+                        method_name == "init",
+                        self.file.clone(),
+                    );
+                    class_methods.insert(method_name.clone(), function);
+                }
+
+                self.environment = enclosing;
+
+                let class = LuxClass::new(name.lexeme.clone(), superclass, class_methods);
+                self.environment
+                    .borrow_mut()
+                    .define(name.lexeme.clone(), Literal::Class(Box::new(class)))
+            }
             Stmt::Return { value, .. } => {
                 let value = self.evaluate(value)?;
-                panic_any(value)
+                return Ok(ControlFlow::Return(value));
             }
         };
-        Ok(())
+        Ok(ControlFlow::Normal)
     }
 
     pub fn execute_block(
         &mut self,
         statements: &[Stmt],
         environment: Rc<RefCell<Environment>>,
-    ) -> RuntimeResult<()> {
+    ) -> RuntimeResult<ControlFlow> {
         let previous = self.environment.clone();
         self.environment = environment;
+
+        let mut result = Ok(ControlFlow::Normal);
         for statement in statements {
-            if self.execute(statement).is_err() {
-                self.environment = previous;
-                return Ok(());
+            result = self.execute(statement);
+            if !matches!(result, Ok(ControlFlow::Normal)) {
+                break;
             }
         }
+
         self.environment = previous;
-        Ok(())
+        result
+    }
+
+    pub fn resolve(&mut self, id: ExprId, slot: Slot) {
+        self.locals.insert(id, slot);
     }
 
-    pub fn resolve(&mut self, expr: &Expr, depth: usize) {
-        self.locals.insert(expr.clone(), depth);
+    /// Records a hint for a name the resolver found no local for, in case it
+    /// turns out not to be a global either.
+    pub fn hint(&mut self, id: ExprId, help: String) {
+        self.hints.insert(id, help);
     }
 
-    fn lookup_variable(&self, name: &Token, expr: &Expr) -> RuntimeResult<Literal> {
-        if let Some(distance) = self.locals.get(expr) {
-            self.environment.borrow().get_at(*distance, &name.lexeme)
+    fn lookup_variable(&self, name: &Token, id: ExprId) -> RuntimeResult<Literal> {
+        if let Some(slot) = self.locals.get(&id) {
+            Ok(Environment::get_at(&self.environment, *slot))
         } else {
-            self.globals.borrow().get(name)
+            let value = Environment::global(&self.environment).borrow().get(name);
+            value.map_err(|err| self.with_suggestion(err, name, id))
         }
     }
+
+    // Suggests a name for an undefined variable: a local in scope where it was
+    // used, or else a global.
+    fn with_suggestion(&self, err: RuntimeError, name: &Token, id: ExprId) -> RuntimeError {
+        let help = self.hints.get(&id).cloned().or_else(|| {
+            Environment::global(&self.environment)
+                .borrow()
+                .suggest(&name.lexeme)
+        });
+        err.with_help(help)
+    }
 }
 
 impl Default for Interpreter {
@@ -0,0 +1,31 @@
+mod callable;
+mod chunk;
+mod class;
+mod clock;
+mod compiler;
+pub mod diagnostic;
+mod disassembler;
+mod environment;
+pub mod error;
+pub mod error_code;
+mod expr;
+mod function;
+mod instance;
+mod interpreter;
+mod list;
+mod literal;
+pub mod lux;
+mod map;
+mod module;
+mod native;
+mod object;
+pub mod parser;
+mod resolver;
+mod runtime_error;
+pub mod scanner;
+pub mod span;
+mod stmt;
+mod suggestion;
+pub mod token;
+pub mod token_type;
+mod vm;
@@ -0,0 +1,100 @@
+use std::{
+    cell::RefCell,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
+
+use crate::{
+    error_code::ErrorCode, interpreter::RuntimeResult, literal::Literal,
+    runtime_error::RuntimeError, token::Token,
+};
+use rand::Rng;
+
+#[derive(Clone, Debug, Eq)]
+pub struct LuxList {
+    elements: Rc<RefCell<Vec<Literal>>>,
+    id: usize,
+}
+
+impl LuxList {
+    pub fn new(elements: Vec<Literal>) -> Self {
+        let mut rng = rand::thread_rng();
+        let id: usize = rng.gen();
+        Self {
+            elements: Rc::new(RefCell::new(elements)),
+            id,
+        }
+    }
+
+    pub fn get(&self, index: &Literal, bracket: &Token) -> RuntimeResult<Literal> {
+        let index = self.index(index, bracket)?;
+        Ok(self.elements.borrow()[index].clone())
+    }
+
+    pub fn set(&self, index: &Literal, value: Literal, bracket: &Token) -> RuntimeResult<()> {
+        let index = self.index(index, bracket)?;
+        self.elements.borrow_mut()[index] = value;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.borrow().len()
+    }
+
+    pub fn push(&self, value: Literal) {
+        self.elements.borrow_mut().push(value);
+    }
+
+    pub fn pop(&self) -> Option<Literal> {
+        self.elements.borrow_mut().pop()
+    }
+
+    pub fn to_str(&self) -> String {
+        let elements: Vec<String> = self
+            .elements
+            .borrow()
+            .iter()
+            .map(|element| element.to_string())
+            .collect();
+        format!("[{}]", elements.join(", "))
+    }
+
+    fn index(&self, index: &Literal, bracket: &Token) -> RuntimeResult<usize> {
+        let number = match index {
+            Literal::Number(number) if number.0 >= 0.0 && number.0.fract() == 0.0 => number.0,
+            _ => {
+                return Err(RuntimeError::new(
+                    bracket.clone(),
+                    ErrorCode::InvalidIndex,
+                    "List index must be a non-negative integer.".to_string(),
+                ))
+            }
+        };
+
+        let len = self.len();
+        if number >= len as f64 {
+            return Err(RuntimeError::new(
+                bracket.clone(),
+                ErrorCode::InvalidIndex,
+                format!(
+                    "List index {} out of range for list of length {}.",
+                    number, len
+                ),
+            ));
+        }
+
+        Ok(number as usize)
+    }
+}
+
+impl PartialEq for LuxList {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Hash for LuxList {
+    fn hash<H: Hasher>(&self, hasher: &mut H) {
+        self.id.hash(hasher);
+    }
+}
@@ -1,12 +1,24 @@
-
 use std::{
     cmp::Ordering,
     fmt,
     hash::{Hash, Hasher},
     ops,
+    rc::Rc,
 };
 
-use crate::{callable::LuxCallable, clock::Clock, function::LuxFunction};
+use crate::{
+    callable::LuxCallable,
+    class::LuxClass,
+    clock::Clock,
+    function::LuxFunction,
+    instance::LuxInstance,
+    list::LuxList,
+    map::LuxMap,
+    module::LuxModule,
+    native::NativeFunction,
+    object::{BoundMethod, Closure, VmClass, VmInstance},
+    runtime_error::ErrorValue,
+};
 #[derive(Debug, Clone, Copy)]
 pub struct Float(pub f64);
 
@@ -20,13 +32,15 @@ impl Eq for Float {}
 
 impl fmt::Display for Float {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        write!(f, "{}", self.0.to_string())
+        write!(f, "{}", self.0)
     }
 }
 
 impl Hash for Float {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.0.to_bits().hash(state);
+        // 0 and -0 are equal, so they have to hash the same.
+        let value = if self.0 == 0.0 { 0.0 } else { self.0 };
+        value.to_bits().hash(state);
     }
 }
 
@@ -84,6 +98,18 @@ pub enum Literal {
     Nil,
     Clock(Clock),
     Function(Box<LuxFunction>),
+    Class(Box<LuxClass>),
+    Instance(Box<LuxInstance>),
+    List(LuxList),
+    Map(LuxMap),
+    Native(Box<NativeFunction>),
+    Error(Box<ErrorValue>),
+    Module(Box<LuxModule>),
+    // Functions, classes and instances of the VM backend.
+    Closure(Rc<Closure>),
+    BoundMethod(Rc<BoundMethod>),
+    VmClass(Rc<VmClass>),
+    VmInstance(Rc<VmInstance>),
 }
 
 impl fmt::Display for Literal {
@@ -103,6 +129,17 @@ impl Literal {
             Literal::Bool(bool) => bool.to_string(),
             Literal::Clock(clock) => clock.to_string(),
             Literal::Function(func) => func.to_str(),
+            Literal::Class(class) => class.to_str(),
+            Literal::Instance(instance) => instance.to_str(),
+            Literal::List(list) => list.to_str(),
+            Literal::Map(map) => map.to_str(),
+            Literal::Native(native) => native.to_str(),
+            Literal::Error(error) => error.to_str(),
+            Literal::Module(module) => module.to_str(),
+            Literal::Closure(closure) => closure.to_str(),
+            Literal::BoundMethod(method) => method.to_str(),
+            Literal::VmClass(class) => class.to_str(),
+            Literal::VmInstance(instance) => instance.to_str(),
         }
     }
 
@@ -1,15 +1,50 @@
 use crate::{
-    interpreter::Interpreter, parser::Parser, resolver::Resolver, scanner::Scanner, stmt::Stmt,
+    compiler::Compiler,
+    diagnostic::{Diagnostic, ErrorFormat, Severity},
+    disassembler::disassemble,
+    error::LuxError,
+    interpreter::{Interpreter, RuntimeResult},
+    parser::Parser,
+    resolver::Resolver,
+    scanner::Scanner,
+    stmt::Stmt,
+    vm::Vm,
 };
 use std::{
     fs::File,
-    io::{self, BufRead, Read},
+    io::{self, BufRead, IsTerminal, Read},
     path::Path,
+    rc::Rc,
 };
 
+/// How resolved statements are run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// Walk the syntax tree.
+    Tree,
+    /// Compile to bytecode and run it on a stack VM.
+    Vm,
+}
+
 pub struct Lux {
     pub had_error: bool,
     pub had_runtime_error: bool,
+    /// Diagnostics are colorized only when stderr, where they are printed, is
+    /// a terminal.
+    pub color: bool,
+    pub error_format: ErrorFormat,
+    /// Whether resolver warnings fail the script like errors.
+    pub deny_warnings: bool,
+    pub backend: Backend,
+    /// Whether to print the bytecode scripts compile to instead of running
+    /// them.
+    pub disassemble: bool,
+}
+
+impl Default for Lux {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Lux {
@@ -17,22 +52,105 @@ impl Lux {
         Lux {
             had_error: false,
             had_runtime_error: false,
+            color: io::stderr().is_terminal(),
+            error_format: ErrorFormat::Human,
+            deny_warnings: false,
+            backend: Backend::Tree,
+            disassemble: false,
         }
     }
 
-    fn run(&mut self, source: &str) -> Vec<Stmt> {
+    fn run(&mut self, path: &Path, source: &str) -> Vec<Stmt> {
         let mut sc = Scanner::new(source.to_string());
         let tokens = sc.scan_tokens().to_owned();
         let mut parser = Parser::new(tokens);
-        let statements = match parser.parse() {
-            Ok(val) => val,
+        let (statements, parse_errors) = parser.parse();
+
+        let mut errors = sc.errors().clone();
+        errors.extend(parse_errors);
+        if !errors.is_empty() {
+            self.had_error = true;
+            errors.sort_by_key(|err| err.span.start);
+            for err in &errors {
+                self.report(Diagnostic::from(err), path, source);
+            }
+            std::process::exit(65)
+        }
+        statements
+    }
+
+    // Diagnostics go to stderr, so they can be read apart from what the
+    // script prints.
+    fn report(&self, diagnostic: Diagnostic, path: &Path, source: &str) {
+        match self.error_format {
+            ErrorFormat::Human => eprint!("{}", diagnostic.render(path, source, self.color)),
+            ErrorFormat::Json => eprintln!("{}", diagnostic.to_json(path, source)),
+        }
+    }
+
+    // Reports the resolver's warnings, as errors when they are denied, and
+    // returns whether there were any.
+    fn report_warnings(&self, resolver: &Resolver, path: &Path, source: &str) -> bool {
+        let mut warnings = resolver.warnings().clone();
+        warnings.sort_by(|a, b| (&a.file, a.span.start).cmp(&(&b.file, b.span.start)));
+        for warning in &warnings {
+            let mut diagnostic = Diagnostic::from(warning);
+            if !self.deny_warnings {
+                diagnostic.severity = Severity::Warning;
+            }
+            self.report(diagnostic, path, source);
+        }
+        !warnings.is_empty()
+    }
+
+    // Reports why resolving failed and exits: with every syntax error of an
+    // imported module that didn't parse, or else with the resolver's error.
+    fn resolve_failed(
+        &mut self,
+        interpreter: &mut Interpreter,
+        err: LuxError,
+        path: &Path,
+        source: &str,
+    ) -> ! {
+        self.had_error = true;
+        let syntax_errors = interpreter.modules.take_syntax_errors();
+        if syntax_errors.is_empty() {
+            self.report(Diagnostic::from(&err), path, source);
+            std::process::exit(75)
+        }
+        for err in &syntax_errors {
+            self.report(Diagnostic::from(err), path, source);
+        }
+        std::process::exit(65)
+    }
+
+    // Runs resolved statements on the chosen backend, or prints what they
+    // compile to.
+    fn execute(
+        &mut self,
+        interpreter: &mut Interpreter,
+        statements: &[Stmt],
+        path: &Path,
+        source: &str,
+    ) -> RuntimeResult<()> {
+        if self.backend == Backend::Tree && !self.disassemble {
+            return interpreter.interpret(statements);
+        }
+
+        let function = match Compiler::compile(statements, interpreter.file.clone(), source) {
+            Ok(function) => function,
             Err(err) => {
                 self.had_error = true;
-                println!("{}", err.to_string());
+                self.report(Diagnostic::from(&err), path, source);
                 std::process::exit(65)
             }
         };
-        statements
+        if self.disassemble {
+            print!("{}", disassemble(&function));
+            Ok(())
+        } else {
+            Vm::new(interpreter).run(function)
+        }
     }
 
     pub fn run_file<P>(&mut self, path: P) -> io::Result<()>
@@ -40,19 +158,27 @@ impl Lux {
         P: AsRef<Path>,
     {
         let path = path.as_ref();
-        let mut file = File::open(&path)?;
+        let mut file = File::open(path)?;
         let mut buffer = String::new();
         file.read_to_string(&mut buffer)?;
         let mut interpreter = Interpreter::new();
+        interpreter.modules.enter(path);
+        interpreter.file = Rc::from(path);
+        if let Some(directory) = path.parent() {
+            interpreter.directory = directory.to_path_buf();
+        }
         let mut resolver = Resolver::new(&mut interpreter);
-        let statements = self.run(&buffer);
-        if let Err(err) = resolver.resolve(&statements) {
-            println!("{}", err.to_string());
+        let statements = self.run(path, &buffer);
+        let resolved = resolver.resolve(&statements);
+        let warned = self.report_warnings(&resolver, path, &buffer);
+        if let Err(err) = resolved {
+            self.resolve_failed(&mut interpreter, err, path, &buffer)
+        } else if warned && self.deny_warnings {
             self.had_error = true;
             std::process::exit(75)
-        } else if let Err(err) = interpreter.interpret(&statements) {
+        } else if let Err(err) = self.execute(&mut interpreter, &statements, path, &buffer) {
             self.had_runtime_error = true;
-            println!("{}", err.to_string());
+            self.report(Diagnostic::from(&err), path, &buffer);
             std::process::exit(70)
         }
 
@@ -63,15 +189,21 @@ impl Lux {
     pub fn run_prompt(&mut self) -> io::Result<()> {
         let stdin = io::stdin();
         let mut interpreter = Interpreter::new();
+        let path = interpreter.file.clone();
         for line_result in stdin.lock().lines() {
             let line = line_result?;
             if line.is_empty() {
                 break;
             }
-            let statements = self.run(&line);
-            if let Err(err) = interpreter.interpret(&statements) {
+            let statements = self.run(&path, &line);
+            // Each line is resolved on its own, so warnings about code that
+            // later lines may use would only be noise.
+            if let Err(err) = Resolver::new(&mut interpreter).resolve(&statements) {
+                self.resolve_failed(&mut interpreter, err, &path, &line)
+            }
+            if let Err(err) = self.execute(&mut interpreter, &statements, &path, &line) {
                 self.had_runtime_error = true;
-                println!("{}", err.to_string());
+                self.report(Diagnostic::from(&err), &path, &line);
                 std::process::exit(70)
             }
             self.had_error = false;
@@ -1,41 +1,58 @@
-mod callable;
-mod clock;
-mod environment;
-pub mod error;
-mod expr;
-mod function;
-mod interpreter;
-mod literal;
-mod lux;
-pub mod parser;
-mod runtime_error;
-pub mod scanner;
-mod stmt;
-pub mod token;
-pub mod token_type;
-mod resolver;
-use lux::Lux;
-use std::env;
+use lux::{
+    diagnostic::ErrorFormat,
+    lux::{Backend, Lux},
+};
+use std::{env, thread};
 use text_colorizer::*;
 
+// Lux calls recurse through `Interpreter::evaluate`, which takes far more
+// stack per call than the main thread has room for at `MAX_CALL_DEPTH`.
+const STACK_SIZE: usize = 512 * 1024 * 1024;
+
 fn main() {
+    let interpreter = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .expect("Failed to start the interpreter thread");
+    if interpreter.join().is_err() {
+        std::process::exit(101);
+    }
+}
+
+fn run() {
     let mut lux = Lux::new();
-    let args: Vec<String> = env::args().skip(1).collect();
+    let (options, args): (Vec<String>, Vec<String>) =
+        env::args().skip(1).partition(|arg| arg.starts_with("--"));
+    for option in &options {
+        match option.as_str() {
+            "--error-format=human" => lux.error_format = ErrorFormat::Human,
+            "--error-format=json" => lux.error_format = ErrorFormat::Json,
+            "--deny-warnings" => lux.deny_warnings = true,
+            "--backend=tree" => lux.backend = Backend::Tree,
+            "--backend=vm" => lux.backend = Backend::Vm,
+            "--disassemble" => lux.disassemble = true,
+            _ => usage(&format!("unknown option '{}'.", option)),
+        }
+    }
     match args.len() {
         0 => lux.run_prompt().unwrap(),
         1 => lux.run_file(&args[0]).unwrap(),
-        _ => {
-            println!("Usage: rslux [script]");
-            eprintln!(
-                "{} wrong number of arguments: expected 1, got {}.",
-                "Error:".red().bold(),
-                args.len()
-            );
-            std::process::exit(64);
-        }
+        _ => usage(&format!(
+            "wrong number of arguments: expected 1, got {}.",
+            args.len()
+        )),
     }
 }
 
+fn usage(error: &str) -> ! {
+    println!(
+        "Usage: rslux [--error-format=human|json] [--deny-warnings] [--backend=tree|vm] \
+         [--disassemble] [script]"
+    );
+    eprintln!("{} {}", "Error:".red().bold(), error);
+    std::process::exit(64);
+}
+
 // if args[0] == "visit" {
 //     let tree = Expr::Binary {
 //         left: Box::new(Expr::Unary {
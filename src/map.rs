@@ -0,0 +1,146 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
+
+use crate::{
+    error_code::ErrorCode, interpreter::RuntimeResult, literal::Literal,
+    runtime_error::RuntimeError, token::Token,
+};
+use rand::Rng;
+
+#[derive(Clone, Debug)]
+pub struct LuxMap {
+    entries: Rc<RefCell<Entries>>,
+    id: usize,
+}
+
+// Entries are kept in the order their keys were first set, so that keys()
+// and printing are the same on every run.
+#[derive(Debug, Default)]
+struct Entries {
+    entries: Vec<(Literal, Literal)>,
+    // Where each key's entry is in `entries`.
+    indices: HashMap<Literal, usize>,
+}
+
+impl LuxMap {
+    pub fn new() -> Self {
+        let mut rng = rand::thread_rng();
+        let id: usize = rng.gen();
+        Self {
+            entries: Rc::new(RefCell::new(Entries::default())),
+            id,
+        }
+    }
+
+    pub fn get(&self, key: &Literal, token: &Token) -> RuntimeResult<Literal> {
+        Self::check_key(key, token)?;
+        let entries = self.entries.borrow();
+        match entries.indices.get(key) {
+            Some(index) => Ok(entries.entries[*index].1.clone()),
+            None => Err(RuntimeError::new(
+                token.clone(),
+                ErrorCode::UndefinedMember,
+                format!("Undefined key '{}'.", key),
+            )),
+        }
+    }
+
+    pub fn set(&self, key: Literal, value: Literal, token: &Token) -> RuntimeResult<()> {
+        Self::check_key(&key, token)?;
+        let mut entries = self.entries.borrow_mut();
+        match entries.indices.get(&key) {
+            Some(index) => {
+                let index = *index;
+                entries.entries[index].1 = value;
+            }
+            None => {
+                let index = entries.entries.len();
+                entries.indices.insert(key.clone(), index);
+                entries.entries.push((key, value));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn has(&self, key: &Literal, token: &Token) -> RuntimeResult<bool> {
+        Self::check_key(key, token)?;
+        Ok(self.entries.borrow().indices.contains_key(key))
+    }
+
+    pub fn remove(&self, key: &Literal, token: &Token) -> RuntimeResult<Option<Literal>> {
+        Self::check_key(key, token)?;
+        let mut entries = self.entries.borrow_mut();
+        let index = match entries.indices.remove(key) {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        let (_, value) = entries.entries.remove(index);
+        for later in entries.indices.values_mut() {
+            if *later > index {
+                *later -= 1;
+            }
+        }
+        Ok(Some(value))
+    }
+
+    pub fn keys(&self) -> Vec<Literal> {
+        let entries = self.entries.borrow();
+        entries.entries.iter().map(|(key, _)| key.clone()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.borrow().entries.len()
+    }
+
+    pub fn to_str(&self) -> String {
+        let entries: Vec<String> = self
+            .entries
+            .borrow()
+            .entries
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect();
+        format!("{{{}}}", entries.join(", "))
+    }
+
+    fn check_key(key: &Literal, token: &Token) -> RuntimeResult<()> {
+        match key {
+            // NaN isn't equal to itself, so it could never be found again.
+            Literal::Number(number) if number.0.is_nan() => Err(RuntimeError::new(
+                token.clone(),
+                ErrorCode::WrongType,
+                "Map keys can't be NaN.".to_string(),
+            )),
+            Literal::String(_) | Literal::Number(_) | Literal::Bool(_) => Ok(()),
+            _ => Err(RuntimeError::new(
+                token.clone(),
+                ErrorCode::WrongType,
+                "Map keys must be strings, numbers or booleans.".to_string(),
+            )),
+        }
+    }
+}
+
+impl Default for LuxMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for LuxMap {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for LuxMap {}
+
+impl Hash for LuxMap {
+    fn hash<H: Hasher>(&self, hasher: &mut H) {
+        self.id.hash(hasher);
+    }
+}
@@ -0,0 +1,340 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs, mem,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    environment::Environment,
+    error::LuxError,
+    error_code::ErrorCode,
+    interpreter::{Interpreter, RuntimeResult},
+    literal::Literal,
+    parser::Parser,
+    resolver::Resolver,
+    runtime_error::RuntimeError,
+    scanner::Scanner,
+    stmt::Stmt,
+    token::Token,
+};
+
+/// The namespace value an `import ... as name` binds: the top-level globals
+/// of an executed module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LuxModule {
+    pub name: String,
+    globals: Rc<RefCell<Environment>>,
+}
+
+impl LuxModule {
+    pub fn get(&self, name: &Token) -> RuntimeResult<Literal> {
+        self.globals.borrow().get(name).map_err(|_| {
+            RuntimeError::new(
+                name.clone(),
+                ErrorCode::UndefinedMember,
+                format!("Module '{}' has no member '{}'.", self.name, name.lexeme),
+            )
+            .with_help(self.globals.borrow().suggest(&name.lexeme))
+        })
+    }
+
+    pub fn to_str(&self) -> String {
+        format!("<module {}>", self.name)
+    }
+}
+
+impl std::hash::Hash for LuxModule {
+    fn hash<H: std::hash::Hasher>(&self, hasher: &mut H) {
+        self.name.hash(hasher);
+    }
+}
+
+/// What `ModuleLoader::begin_import` found: a module that has already run,
+/// or one that has to be run now.
+pub enum Import {
+    Loaded(LuxModule),
+    Run {
+        canonical: PathBuf,
+        statements: Vec<Stmt>,
+        source: String,
+    },
+}
+
+// Why a module couldn't be resolved.
+enum ModuleError {
+    Syntax(Vec<LuxError>),
+    Resolve(LuxError),
+}
+
+enum ModuleState {
+    Resolved(Vec<Stmt>, String),
+    Running,
+    Loaded(LuxModule),
+}
+
+/// Keeps track of every module a program imports, so that each file is loaded
+/// once no matter how many modules import it.
+#[derive(Default)]
+pub struct ModuleLoader {
+    modules: HashMap<PathBuf, ModuleState>,
+    loading: Vec<PathBuf>,
+    /// Warnings from resolving modules, until the importer's resolver takes
+    /// them.
+    warnings: Vec<LuxError>,
+    /// Every syntax error in a module that didn't parse. The resolver stops at
+    /// the first, and the rest are reported with it.
+    syntax_errors: Vec<LuxError>,
+}
+
+impl ModuleLoader {
+    /// Marks the entry script as being loaded so importing it back is a cycle.
+    pub fn enter(&mut self, path: &Path) {
+        if let Ok(canonical) = path.canonicalize() {
+            self.loading.push(canonical);
+        }
+    }
+
+    pub fn take_warnings(&mut self) -> Vec<LuxError> {
+        mem::take(&mut self.warnings)
+    }
+
+    pub fn take_syntax_errors(&mut self) -> Vec<LuxError> {
+        mem::take(&mut self.syntax_errors)
+    }
+
+    /// Scans, parses and resolves the module `path` refers to, unless that has
+    /// already happened, and returns its canonical path.
+    pub fn prepare(interpreter: &mut Interpreter, path: &Token) -> Result<PathBuf, LuxError> {
+        let relative = match &path.literal {
+            Literal::String(relative) => relative,
+            _ => {
+                return Err(LuxError::new(
+                    path,
+                    ErrorCode::UnexpectedToken,
+                    "Expect module path.",
+                ))
+            }
+        };
+
+        let canonical = interpreter
+            .directory
+            .join(relative)
+            .canonicalize()
+            .map_err(|_| {
+                LuxError::new(
+                    path,
+                    ErrorCode::ModuleNotFound,
+                    &format!("Can't find module '{}'.", relative),
+                )
+            })?;
+
+        let loader = &interpreter.modules;
+        if loader.modules.contains_key(&canonical) {
+            return Ok(canonical);
+        }
+
+        if let Some(start) = loader.loading.iter().position(|p| p == &canonical) {
+            let cycle: Vec<String> = loader.loading[start..]
+                .iter()
+                .chain(std::iter::once(&canonical))
+                .map(|p| Self::display_name(p))
+                .collect();
+            return Err(LuxError::new(
+                path,
+                ErrorCode::ImportCycle,
+                &format!("Import cycle detected: {}.", cycle.join(" -> ")),
+            ));
+        }
+
+        let source = fs::read_to_string(&canonical).map_err(|err| {
+            LuxError::new(
+                path,
+                ErrorCode::ModuleNotFound,
+                &format!("Can't read module '{}': {}.", relative, err),
+            )
+        })?;
+
+        interpreter.modules.loading.push(canonical.clone());
+        let importer = Self::enter_module(interpreter, &canonical);
+        let result = Self::resolve_module(interpreter, source.clone());
+        let (_, importer_file) = &importer;
+        let note = format!(
+            "imported from {}:{}:{}",
+            importer_file.display(),
+            path.line,
+            path.column
+        );
+        Self::leave_module(interpreter, importer);
+        interpreter.modules.loading.pop();
+
+        // Errors and warnings in the module are reported against its own
+        // source.
+        let in_module = |mut err: LuxError| {
+            if err.file.is_none() {
+                err.file = Some(Rc::from(canonical.as_path()));
+            }
+            err.notes.push(note.clone());
+            err
+        };
+        let (statements, warnings) = match result {
+            Ok(resolved) => resolved,
+            Err(ModuleError::Syntax(errors)) => {
+                let errors: Vec<LuxError> = errors.into_iter().map(in_module).collect();
+                interpreter
+                    .modules
+                    .syntax_errors
+                    .extend(errors.iter().cloned());
+                return Err(errors[0].clone());
+            }
+            Err(ModuleError::Resolve(err)) => {
+                // A module this one imports didn't parse.
+                for syntax_error in &mut interpreter.modules.syntax_errors {
+                    syntax_error.notes.push(note.clone());
+                }
+                return Err(in_module(err));
+            }
+        };
+        interpreter
+            .modules
+            .warnings
+            .extend(warnings.into_iter().map(in_module));
+        interpreter
+            .modules
+            .modules
+            .insert(canonical.clone(), ModuleState::Resolved(statements, source));
+
+        Ok(canonical)
+    }
+
+    /// Executes the module `path` refers to the first time it is imported and
+    /// returns its namespace.
+    pub fn import(interpreter: &mut Interpreter, path: &Token) -> RuntimeResult<LuxModule> {
+        let (canonical, statements) = match Self::begin_import(interpreter, path)? {
+            Import::Loaded(module) => return Ok(module),
+            Import::Run {
+                canonical,
+                statements,
+                ..
+            } => (canonical, statements),
+        };
+
+        // The module runs with its own globals; anything it defines stays in
+        // its namespace.
+        let globals = Interpreter::new_globals();
+        let environment = mem::replace(&mut interpreter.environment, globals.clone());
+        let enclosing_globals = mem::replace(&mut interpreter.globals, globals.clone());
+        let importer = Self::enter_module(interpreter, &canonical);
+
+        let result = interpreter.interpret(&statements);
+
+        interpreter.environment = environment;
+        interpreter.globals = enclosing_globals;
+        Self::leave_module(interpreter, importer);
+
+        Self::finish_import(interpreter, canonical, globals, result)
+    }
+
+    /// Returns the namespace of the module `path` refers to if it has already
+    /// run, or else its statements for the caller to run, marking it as
+    /// running until `finish_import`.
+    pub fn begin_import(interpreter: &mut Interpreter, path: &Token) -> RuntimeResult<Import> {
+        let canonical = Self::prepare(interpreter, path)
+            .map_err(|err| RuntimeError::new(path.clone(), err.code, err.message))?;
+
+        let state = match interpreter.modules.modules.get_mut(&canonical) {
+            Some(ModuleState::Loaded(module)) => return Ok(Import::Loaded(module.clone())),
+            Some(state) => mem::replace(state, ModuleState::Running),
+            None => ModuleState::Running,
+        };
+
+        match state {
+            ModuleState::Resolved(statements, source) => Ok(Import::Run {
+                canonical,
+                statements,
+                source,
+            }),
+            _ => Err(RuntimeError::new(
+                path.clone(),
+                ErrorCode::ImportCycle,
+                format!(
+                    "Import cycle detected: '{}' is still being loaded.",
+                    Self::display_name(&canonical)
+                ),
+            )),
+        }
+    }
+
+    /// Records how running a module `begin_import` returned went, and returns
+    /// its namespace of `globals` if it succeeded.
+    pub fn finish_import(
+        interpreter: &mut Interpreter,
+        canonical: PathBuf,
+        globals: Rc<RefCell<Environment>>,
+        result: RuntimeResult<()>,
+    ) -> RuntimeResult<LuxModule> {
+        if let Err(err) = result {
+            interpreter.modules.modules.remove(&canonical);
+            return Err(err.in_file(&Rc::from(canonical.as_path())));
+        }
+
+        let module = LuxModule {
+            name: Self::display_name(&canonical),
+            globals,
+        };
+        interpreter
+            .modules
+            .modules
+            .insert(canonical, ModuleState::Loaded(module.clone()));
+
+        Ok(module)
+    }
+
+    fn resolve_module(
+        interpreter: &mut Interpreter,
+        source: String,
+    ) -> Result<(Vec<Stmt>, Vec<LuxError>), ModuleError> {
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().to_owned();
+        let (statements, parse_errors) = Parser::new(tokens).parse();
+
+        let mut errors = scanner.errors().clone();
+        errors.extend(parse_errors);
+        if !errors.is_empty() {
+            errors.sort_by_key(|err| err.span.start);
+            return Err(ModuleError::Syntax(errors));
+        }
+
+        let mut resolver = Resolver::for_module(interpreter);
+        resolver
+            .resolve(&statements)
+            .map_err(ModuleError::Resolve)?;
+        let warnings = resolver.warnings().clone();
+        Ok((statements, warnings))
+    }
+
+    // Points the interpreter at the module's file and directory, and returns
+    // the ones it replaced.
+    pub fn enter_module(interpreter: &mut Interpreter, canonical: &Path) -> (PathBuf, Rc<Path>) {
+        let directory = canonical
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| interpreter.directory.clone());
+        (
+            mem::replace(&mut interpreter.directory, directory),
+            mem::replace(&mut interpreter.file, Rc::from(canonical)),
+        )
+    }
+
+    pub fn leave_module(interpreter: &mut Interpreter, (directory, file): (PathBuf, Rc<Path>)) {
+        interpreter.directory = directory;
+        interpreter.file = file;
+    }
+
+    fn display_name(path: &Path) -> String {
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string())
+    }
+}
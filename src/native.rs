@@ -0,0 +1,163 @@
+use rand::Rng;
+use std::hash::{Hash, Hasher};
+
+use crate::{
+    callable::LuxCallable,
+    error_code::ErrorCode,
+    interpreter::{Interpreter, RuntimeResult},
+    list::LuxList,
+    literal::{Float, Literal},
+    runtime_error::RuntimeError,
+    token::Token,
+};
+
+type NativeFn = fn(Vec<Literal>, &Token) -> RuntimeResult<Literal>;
+
+#[derive(Clone, Debug, Eq)]
+pub struct NativeFunction {
+    pub name: &'static str,
+    arity: usize,
+    function: NativeFn,
+    id: usize,
+}
+
+impl LuxCallable for NativeFunction {
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: Vec<Literal>,
+        paren: &Token,
+    ) -> RuntimeResult<Literal> {
+        (self.function)(arguments, paren)
+    }
+
+    fn to_str(&self) -> String {
+        "<native fn>".to_string()
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn id(&self) -> usize {
+        self.id
+    }
+}
+
+impl NativeFunction {
+    pub fn new(name: &'static str, arity: usize, function: NativeFn) -> Self {
+        let mut rng = rand::thread_rng();
+        let id: usize = rng.gen();
+        Self {
+            name,
+            arity,
+            function,
+            id,
+        }
+    }
+
+    /// Every native function that is defined in the global scope.
+    pub fn globals() -> Vec<NativeFunction> {
+        vec![
+            NativeFunction::new("len", 1, len),
+            NativeFunction::new("push", 2, push),
+            NativeFunction::new("pop", 1, pop),
+            NativeFunction::new("has", 2, has),
+            NativeFunction::new("remove", 2, remove),
+            NativeFunction::new("keys", 1, keys),
+        ]
+    }
+}
+
+impl PartialEq for NativeFunction {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Hash for NativeFunction {
+    fn hash<H: Hasher>(&self, hasher: &mut H) {
+        self.id.hash(hasher);
+    }
+}
+
+fn len(arguments: Vec<Literal>, paren: &Token) -> RuntimeResult<Literal> {
+    let len = match &arguments[0] {
+        Literal::List(list) => list.len(),
+        Literal::Map(map) => map.len(),
+        Literal::String(string) => string.chars().count(),
+        _ => {
+            return Err(RuntimeError::new(
+                paren.clone(),
+                ErrorCode::WrongType,
+                "len() expects a list, a map or a string.".to_string(),
+            ))
+        }
+    };
+
+    Ok(Literal::Number(Float(len as f64)))
+}
+
+fn push(arguments: Vec<Literal>, paren: &Token) -> RuntimeResult<Literal> {
+    match &arguments[0] {
+        Literal::List(list) => {
+            list.push(arguments[1].clone());
+            Ok(Literal::Nil)
+        }
+        _ => Err(RuntimeError::new(
+            paren.clone(),
+            ErrorCode::WrongType,
+            "push() expects a list as its first argument.".to_string(),
+        )),
+    }
+}
+
+fn pop(arguments: Vec<Literal>, paren: &Token) -> RuntimeResult<Literal> {
+    match &arguments[0] {
+        Literal::List(list) => list.pop().ok_or_else(|| {
+            RuntimeError::new(
+                paren.clone(),
+                ErrorCode::Runtime,
+                "Can't pop from an empty list.".to_string(),
+            )
+        }),
+        _ => Err(RuntimeError::new(
+            paren.clone(),
+            ErrorCode::WrongType,
+            "pop() expects a list.".to_string(),
+        )),
+    }
+}
+
+fn has(arguments: Vec<Literal>, paren: &Token) -> RuntimeResult<Literal> {
+    match &arguments[0] {
+        Literal::Map(map) => Ok(Literal::Bool(map.has(&arguments[1], paren)?)),
+        _ => Err(RuntimeError::new(
+            paren.clone(),
+            ErrorCode::WrongType,
+            "has() expects a map as its first argument.".to_string(),
+        )),
+    }
+}
+
+fn remove(arguments: Vec<Literal>, paren: &Token) -> RuntimeResult<Literal> {
+    match &arguments[0] {
+        Literal::Map(map) => Ok(map.remove(&arguments[1], paren)?.unwrap_or(Literal::Nil)),
+        _ => Err(RuntimeError::new(
+            paren.clone(),
+            ErrorCode::WrongType,
+            "remove() expects a map as its first argument.".to_string(),
+        )),
+    }
+}
+
+fn keys(arguments: Vec<Literal>, paren: &Token) -> RuntimeResult<Literal> {
+    match &arguments[0] {
+        Literal::Map(map) => Ok(Literal::List(LuxList::new(map.keys()))),
+        _ => Err(RuntimeError::new(
+            paren.clone(),
+            ErrorCode::WrongType,
+            "keys() expects a map.".to_string(),
+        )),
+    }
+}
@@ -0,0 +1,155 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    hash::{Hash, Hasher},
+    path::Path,
+    rc::Rc,
+};
+
+use crate::{chunk::Chunk, environment::Environment, literal::Literal};
+
+/// What a compiled function is, which decides what its slot 0 holds and what
+/// it returns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FunctionKind {
+    /// The top level of a script or module.
+    Script,
+    Function,
+    Method,
+    Initializer,
+}
+
+/// A function compiled to bytecode, shared by every closure made from it.
+pub struct FunctionProto {
+    /// `None` for lambdas.
+    pub name: Option<String>,
+    pub kind: FunctionKind,
+    pub arity: usize,
+    pub upvalues: usize,
+    pub chunk: Chunk,
+    /// The file the function is declared in, for errors raised in its body.
+    pub file: Rc<Path>,
+}
+
+impl FunctionProto {
+    /// The name stack traces show for the function.
+    pub fn name(&self) -> String {
+        match (&self.name, self.kind) {
+            (_, FunctionKind::Script) => "<script>".to_string(),
+            (Some(name), _) => name.clone(),
+            (None, _) => "<lambda>".to_string(),
+        }
+    }
+
+    pub fn to_str(&self) -> String {
+        match (&self.name, self.kind) {
+            (Some(name), FunctionKind::Function)
+            | (Some(name), FunctionKind::Method)
+            | (Some(name), FunctionKind::Initializer) => format!("<fn {}>", name),
+            _ => self.name(),
+        }
+    }
+}
+
+/// A variable a closure captured: still in its slot on the VM's stack while
+/// the function declaring it runs, and moved into the upvalue after.
+#[derive(Debug)]
+pub enum Upvalue {
+    Open(usize),
+    Closed(Literal),
+}
+
+pub struct Closure {
+    pub function: Rc<FunctionProto>,
+    pub upvalues: Vec<Rc<RefCell<Upvalue>>>,
+    /// The globals of the module the function was declared in.
+    pub globals: Rc<RefCell<Environment>>,
+}
+
+impl Closure {
+    pub fn to_str(&self) -> String {
+        self.function.to_str()
+    }
+}
+
+/// A method looked up on an instance, which runs with `this` bound to it.
+pub struct BoundMethod {
+    pub receiver: Literal,
+    pub method: Rc<Closure>,
+}
+
+impl BoundMethod {
+    pub fn to_str(&self) -> String {
+        self.method.to_str()
+    }
+}
+
+pub struct VmClass {
+    pub name: String,
+    pub methods: RefCell<HashMap<String, Rc<Closure>>>,
+}
+
+impl VmClass {
+    pub fn new(name: String) -> Self {
+        VmClass {
+            name,
+            methods: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn find_method(&self, name: &str) -> Option<Rc<Closure>> {
+        self.methods.borrow().get(name).cloned()
+    }
+
+    pub fn to_str(&self) -> String {
+        self.name.clone()
+    }
+}
+
+pub struct VmInstance {
+    pub class: Rc<VmClass>,
+    pub fields: RefCell<HashMap<String, Literal>>,
+}
+
+impl VmInstance {
+    pub fn new(class: Rc<VmClass>) -> Self {
+        VmInstance {
+            class,
+            fields: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn to_str(&self) -> String {
+        format!("{} instance", self.class.name)
+    }
+}
+
+// Objects are equal only to themselves. They are printed by name rather than
+// derived `Debug`, which would recurse forever through a closure that
+// captures itself.
+macro_rules! identity {
+    ($($object:ty),*) => {$(
+        impl PartialEq for $object {
+            fn eq(&self, other: &Self) -> bool {
+                std::ptr::eq(self, other)
+            }
+        }
+
+        impl Eq for $object {}
+
+        impl Hash for $object {
+            fn hash<H: Hasher>(&self, hasher: &mut H) {
+                std::ptr::hash(self, hasher);
+            }
+        }
+
+        impl fmt::Debug for $object {
+            fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+                write!(f, "{}", self.to_str())
+            }
+        }
+    )*};
+}
+
+identity!(Closure, BoundMethod, VmClass, VmInstance);
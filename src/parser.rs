@@ -1,34 +1,81 @@
 use crate::{
-    error::LuxError, expr::Expr, function::Function, literal::Literal, stmt::Stmt, token::Token,
+    error::LuxError,
+    error_code::ErrorCode,
+    expr::{Expr, ExprId},
+    function::Function,
+    literal::Literal,
+    span::Span,
+    stmt::Stmt,
+    token::Token,
     token_type::Types,
 };
 
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    errors: Vec<LuxError>,
+    // How many blocks the parser is inside of, so that `synchronize` knows
+    // whether a '}' closes one.
+    block_depth: usize,
 }
 
 type ParserResult<T> = Result<T, LuxError>;
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Parser { current: 0, tokens }
+        Parser {
+            current: 0,
+            tokens,
+            errors: Vec::new(),
+            block_depth: 0,
+        }
     }
 
-    pub fn parse(&mut self) -> ParserResult<Vec<Stmt>> {
+    /// Parses every statement it can, recovering after each syntax error, and
+    /// returns them along with all the errors met. Statements with errors are
+    /// left out.
+    pub fn parse(&mut self) -> (Vec<Stmt>, Vec<LuxError>) {
         let mut statements = Vec::<Stmt>::new();
         while !self.is_at_end() {
-            let stmt = self.decleration()?;
-            statements.push(stmt);
+            if let Some(stmt) = self.recovering_decleration() {
+                statements.push(stmt);
+            }
+        }
+        (statements, std::mem::take(&mut self.errors))
+    }
+
+    fn recovering_decleration(&mut self) -> Option<Stmt> {
+        let start = self.current;
+        match self.decleration() {
+            Ok(stmt) => Some(stmt),
+            Err(err) => {
+                // A statement that fails on or after text the scanner
+                // couldn't scan fails because of it, and the scanner has
+                // already reported that.
+                let scanned_badly = self.tokens[start..]
+                    .iter()
+                    .take_while(|token| token.span.start <= err.span.start)
+                    .any(|token| token.type_t == Types::ERROR);
+                if !scanned_badly {
+                    self.errors.push(err);
+                }
+                None
+            }
         }
-        Ok(statements)
     }
 
     fn decleration(&mut self) -> ParserResult<Stmt> {
-        let decleration = if self.matches(vec![Types::FUN]) {
-            self.function("function")
+        let decleration = if self.matches(vec![Types::CLASS]) {
+            self.class_decleration()
+        } else if self.check(Types::FUN) && self.check_ahead(1, Types::IDENTIFIER) {
+            let start = self.advance().span;
+            self.function("function", start).map(Stmt::Function)
         } else if self.matches(vec![Types::VAR]) {
             self.var_decleration()
+        } else if self.matches(vec![Types::IMPORT]) {
+            self.import_decleration()
+        } else if self.matches(vec![Types::FROM]) {
+            self.selective_import_decleration()
         } else {
             self.statement()
         };
@@ -38,20 +85,123 @@ impl Parser {
         decleration
     }
 
-    fn function(&mut self, kind: &str) -> ParserResult<Stmt> {
+    fn class_decleration(&mut self) -> ParserResult<Stmt> {
+        let start = self.previous().span;
+        let name = self
+            .consume(Types::IDENTIFIER, "Expect class name.")?
+            .clone();
+
+        let superclass = if self.matches(vec![Types::LESS]) {
+            let name = self
+                .consume(Types::IDENTIFIER, "Expect superclass name.")?
+                .clone();
+            Some(Box::new(Expr::Variable {
+                id: ExprId::fresh(),
+                name,
+            }))
+        } else {
+            None
+        };
+
+        self.consume(Types::LEFT_BRACE, "Expect '{' before class body.")?;
+
+        let mut methods: Vec<Function> = vec![];
+        while !self.check(Types::RIGHT_BRACE) && !self.is_at_end() {
+            let start = self.peek().span;
+            methods.push(self.function("method", start)?);
+        }
+
+        self.consume(Types::RIGHT_BRACE, "Expect '}' after class body.")?;
+
+        Ok(Stmt::Class {
+            name,
+            superclass,
+            methods,
+            span: self.span_from(start),
+        })
+    }
+
+    fn function(&mut self, kind: &str, start: Span) -> ParserResult<Function> {
         let name = self
             .consume(Types::IDENTIFIER, &format!("Expect {} name.", kind))?
             .clone();
-        self.consume(Types::LEFT_PAREN, "Expect '(' after 'if'.")?;
+        self.consume(
+            Types::LEFT_PAREN,
+            &format!("Expect '(' after {} name.", kind),
+        )?;
+        let parameters = self.parameters()?;
+        self.consume(
+            Types::LEFT_BRACE,
+            &format!("Expect '{{' before {} body.", kind),
+        )?;
+        let body = self.block()?;
+        Ok(Function {
+            body,
+            name: Some(name),
+            param: parameters,
+            span: self.span_from(start),
+        })
+    }
+
+    fn lambda(&mut self) -> ParserResult<Expr> {
+        let keyword = self.previous().clone();
+        self.consume(Types::LEFT_PAREN, "Expect '(' after 'fun'.")?;
+        let parameters = self.parameters()?;
+        self.consume(Types::LEFT_BRACE, "Expect '{' before lambda body.")?;
+        let body = self.block()?;
+        Ok(Expr::Lambda {
+            id: ExprId::fresh(),
+            function: Function {
+                body,
+                span: self.span_from(keyword.span),
+                name: None,
+                param: parameters,
+            },
+        })
+    }
+
+    fn arrow_lambda(&mut self) -> ParserResult<Expr> {
+        let paren = self.previous().clone();
+        let parameters = self.parameters()?;
+        let arrow = self
+            .consume(Types::ARROW, "Expect '=>' after lambda parameters.")?
+            .clone();
+        let body = if self.matches(vec![Types::LEFT_BRACE]) {
+            self.block()?
+        } else {
+            let value = self.expression()?;
+            vec![Stmt::Return {
+                span: arrow.span.to(value.span()),
+                keyword: arrow,
+                value: Box::new(value),
+            }]
+        };
+        Ok(Expr::Lambda {
+            id: ExprId::fresh(),
+            function: Function {
+                body,
+                span: self.span_from(paren.span),
+                name: None,
+                param: parameters,
+            },
+        })
+    }
+
+    // Parses a parameter list up to and including the closing ')'.
+    fn parameters(&mut self) -> ParserResult<Vec<Token>> {
         let mut parameters: Vec<Token> = vec![];
         if !self.check(Types::RIGHT_PAREN) {
             loop {
                 if parameters.len() >= 255 {
-                    self.error(self.peek(), "Can't have more than 255 parameters.");
-                } else {
-                    let token = self.consume(Types::IDENTIFIER, "Expect parameter name.")?;
-                    parameters.push(token.clone());
+                    let err = self.error(
+                        self.peek(),
+                        ErrorCode::TooMany,
+                        "Can't have more than 255 parameters.",
+                    );
+                    self.errors.push(err);
                 }
+                let token = self.consume(Types::IDENTIFIER, "Expect parameter name.")?;
+                parameters.push(token.clone());
 
                 if !self.matches(vec![Types::COMMA]) {
                     break;
@@ -60,19 +210,59 @@ impl Parser {
         }
 
         self.consume(Types::RIGHT_PAREN, "Expect ')' after parameters.")?;
-        self.consume(
-            Types::LEFT_BRACE,
-            &format!("Expect '{{' before {} body.", kind),
-        )?;
-        let body = self.block()?;
-        Ok(Stmt::Function(Function {
-            body,
-            name,
-            param: parameters,
-        }))
+        Ok(parameters)
+    }
+
+    fn import_decleration(&mut self) -> ParserResult<Stmt> {
+        let start = self.previous().span;
+        let path = self
+            .consume(Types::STRING, "Expect module path after 'import'.")?
+            .clone();
+        let alias = if self.matches(vec![Types::AS]) {
+            Some(
+                self.consume(Types::IDENTIFIER, "Expect module name after 'as'.")?
+                    .clone(),
+            )
+        } else {
+            None
+        };
+        self.consume(Types::SEMICOLON, "Expect ';' after import.")?;
+
+        Ok(Stmt::Import {
+            path,
+            alias,
+            names: vec![],
+            span: self.span_from(start),
+        })
+    }
+
+    fn selective_import_decleration(&mut self) -> ParserResult<Stmt> {
+        let start = self.previous().span;
+        let path = self
+            .consume(Types::STRING, "Expect module path after 'from'.")?
+            .clone();
+        self.consume(Types::IMPORT, "Expect 'import' after module path.")?;
+
+        let mut names: Vec<Token> = vec![];
+        loop {
+            let name = self.consume(Types::IDENTIFIER, "Expect name to import.")?;
+            names.push(name.clone());
+            if !self.matches(vec![Types::COMMA]) {
+                break;
+            }
+        }
+        self.consume(Types::SEMICOLON, "Expect ';' after import.")?;
+
+        Ok(Stmt::Import {
+            path,
+            alias: None,
+            names,
+            span: self.span_from(start),
+        })
     }
 
     fn var_decleration(&mut self) -> ParserResult<Stmt> {
+        let start = self.previous().span;
         let name = self
             .consume(Types::IDENTIFIER, "Expect a variable name.")?
             .clone();
@@ -87,15 +277,19 @@ impl Parser {
         Ok(Stmt::Var {
             name,
             initializer: Box::new(initializer),
+            span: self.span_from(start),
         })
     }
 
     fn statement(&mut self) -> ParserResult<Stmt> {
         if self.matches(vec![Types::PRINT]) {
             self.print_statement()
-        } else if self.matches(vec![Types::LEFT_BRACE]) {
+        } else if !self.is_map_literal() && self.matches(vec![Types::LEFT_BRACE]) {
+            let start = self.previous().span;
+            let statements = self.block()?;
             Ok(Stmt::Block {
-                statements: self.block()?,
+                statements,
+                span: self.span_from(start),
             })
         } else if self.matches(vec![Types::IF]) {
             self.if_statement()
@@ -105,6 +299,31 @@ impl Parser {
             self.for_statement()
         } else if self.matches(vec![Types::RETURN]) {
             self.return_statement()
+        } else if self.matches(vec![Types::THROW]) {
+            let keyword = self.previous().clone();
+            let value = self.expression()?;
+            self.consume(Types::SEMICOLON, "Expect ';' after thrown value.")?;
+            Ok(Stmt::Throw {
+                span: self.span_from(keyword.span),
+                keyword,
+                value: Box::new(value),
+            })
+        } else if self.matches(vec![Types::TRY]) {
+            self.try_statement()
+        } else if self.matches(vec![Types::BREAK]) {
+            let keyword = self.previous().clone();
+            self.consume(Types::SEMICOLON, "Expect ';' after 'break'.")?;
+            Ok(Stmt::Break {
+                span: self.span_from(keyword.span),
+                keyword,
+            })
+        } else if self.matches(vec![Types::CONTINUE]) {
+            let keyword = self.previous().clone();
+            self.consume(Types::SEMICOLON, "Expect ';' after 'continue'.")?;
+            Ok(Stmt::Continue {
+                span: self.span_from(keyword.span),
+                keyword,
+            })
         } else {
             self.expression_statement()
         }
@@ -122,12 +341,54 @@ impl Parser {
         self.consume(Types::SEMICOLON, "Expect ';' after return value.")?;
 
         Ok(Stmt::Return {
+            span: self.span_from(keyword.span),
             keyword,
             value: Box::new(value),
         })
     }
 
+    fn try_statement(&mut self) -> ParserResult<Stmt> {
+        let start = self.previous().span;
+        self.consume(Types::LEFT_BRACE, "Expect '{' after 'try'.")?;
+        let body = self.block()?;
+
+        let catch = if self.matches(vec![Types::CATCH]) {
+            self.consume(Types::LEFT_PAREN, "Expect '(' after 'catch'.")?;
+            let name = self
+                .consume(Types::IDENTIFIER, "Expect exception variable name.")?
+                .clone();
+            self.consume(Types::RIGHT_PAREN, "Expect ')' after exception variable.")?;
+            self.consume(Types::LEFT_BRACE, "Expect '{' before catch body.")?;
+            Some((name, self.block()?))
+        } else {
+            None
+        };
+
+        let finally = if self.matches(vec![Types::FINALLY]) {
+            self.consume(Types::LEFT_BRACE, "Expect '{' after 'finally'.")?;
+            Some(self.block()?)
+        } else {
+            None
+        };
+
+        if catch.is_none() && finally.is_none() {
+            return Err(self.error(
+                self.peek(),
+                ErrorCode::UnexpectedToken,
+                "Expect 'catch' or 'finally' after try block.",
+            ));
+        }
+
+        Ok(Stmt::Try {
+            body,
+            catch,
+            finally,
+            span: self.span_from(start),
+        })
+    }
+
     fn for_statement(&mut self) -> ParserResult<Stmt> {
+        let start = self.previous().span;
         self.consume(Types::LEFT_PAREN, "Expect '(' after 'if'.")?;
 
         let initializer = if self.matches(vec![Types::SEMICOLON]) {
@@ -154,49 +415,57 @@ impl Parser {
         self.consume(Types::RIGHT_PAREN, "Expect ')' after for clauses.")?;
 
         let mut body = self.statement()?;
+        let span = self.span_from(start);
 
-        if let Some(inc) = increment {
-            body = Stmt::Block {
-                statements: vec![
-                    body,
-                    Stmt::Expression {
-                        expression: Box::new(inc),
-                    },
-                ],
-            }
-        }
+        // The increment is kept on the loop rather than appended to the body
+        // so that `continue` still runs it.
+        let increment = increment.map(Box::new);
 
         if let Some(cnd) = condition {
             body = Stmt::While {
                 condition: Box::new(cnd),
                 body: Box::new(body),
+                increment,
+                span,
             }
         } else {
             body = Stmt::While {
                 body: Box::new(body),
                 condition: Box::new(Expr::Literal {
+                    id: ExprId::fresh(),
                     value: Literal::Bool(true),
+                    span,
                 }),
+                increment,
+                span,
             }
         }
 
         if let Some(initializer) = initializer {
             body = Stmt::Block {
                 statements: vec![initializer, body],
+                span,
             }
         }
         Ok(body)
     }
 
     fn while_statement(&mut self) -> ParserResult<Stmt> {
+        let start = self.previous().span;
         self.consume(Types::LEFT_PAREN, "Expect '(' after 'if'.")?;
         let condition = Box::new(self.expression()?);
         self.consume(Types::RIGHT_PAREN, "Expect ')' after 'if'.")?;
         let body = Box::new(self.statement()?);
-        Ok(Stmt::While { condition, body })
+        Ok(Stmt::While {
+            condition,
+            body,
+            increment: None,
+            span: self.span_from(start),
+        })
     }
 
     fn if_statement(&mut self) -> ParserResult<Stmt> {
+        let start = self.previous().span;
         self.consume(Types::LEFT_PAREN, "Expect '(' after 'if'.")?;
         let condition = Box::new(self.expression()?);
         self.consume(Types::RIGHT_PAREN, "Expect ')' after 'if'.")?;
@@ -211,33 +480,42 @@ impl Parser {
             condition,
             else_branch,
             then_branch,
+            span: self.span_from(start),
         })
     }
 
     fn block(&mut self) -> ParserResult<Vec<Stmt>> {
         let mut statements: Vec<Stmt> = Vec::new();
 
+        self.block_depth += 1;
         while !self.check(Types::RIGHT_BRACE) && !self.is_at_end() {
-            statements.push(self.decleration()?)
+            if let Some(stmt) = self.recovering_decleration() {
+                statements.push(stmt);
+            }
         }
+        self.block_depth -= 1;
 
         self.consume(Types::RIGHT_BRACE, "Expect '}' after block.")?;
         Ok(statements)
     }
 
     fn print_statement(&mut self) -> ParserResult<Stmt> {
+        let start = self.previous().span;
         let expr = self.expression()?;
         self.consume(Types::SEMICOLON, "Expect ';' after value")?;
         Ok(Stmt::Print {
             expression: Box::new(expr),
+            span: self.span_from(start),
         })
     }
 
     fn expression_statement(&mut self) -> ParserResult<Stmt> {
+        let start = self.peek().span;
         let expr = self.expression()?;
         self.consume(Types::SEMICOLON, "Expect ';' after value")?;
         Ok(Stmt::Expression {
             expression: Box::new(expr),
+            span: self.span_from(start),
         })
     }
 
@@ -247,6 +525,26 @@ impl Parser {
         loop {
             if self.matches(vec![Types::LEFT_PAREN]) {
                 expr = self.finish_call(expr)?;
+            } else if self.matches(vec![Types::LEFT_BRACKET]) {
+                let index = self.expression()?;
+                let bracket = self
+                    .consume(Types::RIGHT_BRACKET, "Expect ']' after index.")?
+                    .clone();
+                expr = Expr::Index {
+                    id: ExprId::fresh(),
+                    object: Box::new(expr),
+                    bracket,
+                    index: Box::new(index),
+                };
+            } else if self.matches(vec![Types::DOT]) {
+                let name = self
+                    .consume(Types::IDENTIFIER, "Expect property name after '.'.")?
+                    .clone();
+                expr = Expr::Get {
+                    id: ExprId::fresh(),
+                    object: Box::new(expr),
+                    name,
+                };
             } else {
                 break;
             }
@@ -261,7 +559,12 @@ impl Parser {
         if !self.check(Types::RIGHT_PAREN) {
             loop {
                 if arguments.len() >= 255 {
-                    self.error(self.peek(), "Can't have more than 255 arguments.");
+                    let err = self.error(
+                        self.peek(),
+                        ErrorCode::TooMany,
+                        "Can't have more than 255 arguments.",
+                    );
+                    self.errors.push(err);
                 }
                 arguments.push(self.expression()?);
                 if !self.matches(vec![Types::COMMA]) {
@@ -273,6 +576,7 @@ impl Parser {
         let paren = self.consume(Types::RIGHT_PAREN, "Expect ')' after arguments.")?;
 
         Ok(Expr::Call {
+            id: ExprId::fresh(),
             arguments,
             callee: Box::new(callee),
             paren: paren.clone(),
@@ -288,16 +592,45 @@ impl Parser {
 
         if self.matches(vec![Types::EQUAL]) {
             match expr {
-                Expr::Variable { name } => {
+                Expr::Variable { name, .. } => {
                     let value = self.assignment()?;
                     return Ok(Expr::Assign {
+                        id: ExprId::fresh(),
                         name,
                         value: Box::new(value),
                     });
                 }
+                Expr::Get { object, name, .. } => {
+                    let value = self.assignment()?;
+                    return Ok(Expr::Set {
+                        id: ExprId::fresh(),
+                        object,
+                        name,
+                        value: Box::new(value),
+                    });
+                }
+                Expr::Index {
+                    object,
+                    bracket,
+                    index,
+                    ..
+                } => {
+                    let value = self.assignment()?;
+                    return Ok(Expr::SetIndex {
+                        id: ExprId::fresh(),
+                        object,
+                        bracket,
+                        index,
+                        value: Box::new(value),
+                    });
+                }
                 _ => {
                     let equals = self.previous();
-                    return Err(self.error(equals, "Invalid assignment target."));
+                    return Err(self.error(
+                        equals,
+                        ErrorCode::InvalidAssignmentTarget,
+                        "Invalid assignment target.",
+                    ));
                 }
             }
         }
@@ -312,6 +645,7 @@ impl Parser {
             let right = self.and()?;
 
             expr = Expr::Logical {
+                id: ExprId::fresh(),
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
@@ -327,6 +661,7 @@ impl Parser {
             let operator = self.previous().clone();
             let right = self.equality()?;
             expr = Expr::Logical {
+                id: ExprId::fresh(),
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
@@ -342,6 +677,7 @@ impl Parser {
             let operator = self.previous().clone();
             let right = self.comparison()?;
             expr = Expr::Binary {
+                id: ExprId::fresh(),
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
@@ -362,6 +698,7 @@ impl Parser {
             let operator = self.previous().clone();
             let right = self.term()?;
             expr = Expr::Binary {
+                id: ExprId::fresh(),
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
@@ -377,6 +714,7 @@ impl Parser {
             let operator = self.previous().clone();
             let right = self.factor()?;
             expr = Expr::Binary {
+                id: ExprId::fresh(),
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
@@ -392,6 +730,7 @@ impl Parser {
             let operator = self.previous().clone();
             let right = self.unary()?;
             expr = Expr::Binary {
+                id: ExprId::fresh(),
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
@@ -405,6 +744,7 @@ impl Parser {
             let operator = self.previous().clone();
             let right = self.unary()?;
             return Ok(Expr::Unary {
+                id: ExprId::fresh(),
                 operator,
                 right: Box::new(right),
             });
@@ -415,53 +755,225 @@ impl Parser {
     fn primary(&mut self) -> ParserResult<Expr> {
         if self.matches(vec![Types::FALSE]) {
             Ok(Expr::Literal {
+                id: ExprId::fresh(),
                 value: Literal::Bool(false),
+                span: self.previous().span,
             })
         } else if self.matches(vec![Types::TRUE]) {
             Ok(Expr::Literal {
+                id: ExprId::fresh(),
                 value: Literal::Bool(true),
+                span: self.previous().span,
             })
         } else if self.matches(vec![Types::NIL]) {
             Ok(Expr::Literal {
+                id: ExprId::fresh(),
                 value: Literal::Nil,
+                span: self.previous().span,
             })
         } else if self.matches(vec![Types::NUMBER, Types::STRING]) {
             Ok(Expr::Literal {
+                id: ExprId::fresh(),
                 value: self.previous().literal.clone(),
+                span: self.previous().span,
+            })
+        } else if self.matches(vec![Types::INTERPOLATION]) {
+            self.interpolation()
+        } else if self.matches(vec![Types::LEFT_BRACKET]) {
+            let start = self.previous().span;
+            let mut elements: Vec<Expr> = Vec::new();
+            if !self.check(Types::RIGHT_BRACKET) {
+                loop {
+                    elements.push(self.expression()?);
+                    if !self.matches(vec![Types::COMMA]) {
+                        break;
+                    }
+                }
+            }
+            self.consume(Types::RIGHT_BRACKET, "Expect ']' after list elements.")?;
+            Ok(Expr::List {
+                id: ExprId::fresh(),
+                elements,
+                span: self.span_from(start),
+            })
+        } else if self.matches(vec![Types::LEFT_BRACE]) {
+            let brace = self.previous().clone();
+            let mut entries: Vec<(Expr, Expr)> = Vec::new();
+            if !self.check(Types::RIGHT_BRACE) {
+                loop {
+                    let key = self.expression()?;
+                    self.consume(Types::COLON, "Expect ':' after map key.")?;
+                    let value = self.expression()?;
+                    entries.push((key, value));
+                    if !self.matches(vec![Types::COMMA]) {
+                        break;
+                    }
+                }
+            }
+            self.consume(Types::RIGHT_BRACE, "Expect '}' after map entries.")?;
+            Ok(Expr::Map {
+                id: ExprId::fresh(),
+                span: self.span_from(brace.span),
+                brace,
+                entries,
             })
+        } else if self.matches(vec![Types::FUN]) {
+            self.lambda()
+        } else if self.is_arrow_lambda() && self.matches(vec![Types::LEFT_PAREN]) {
+            self.arrow_lambda()
         } else if self.matches(vec![Types::LEFT_PAREN]) {
+            let start = self.previous().span;
             let expr = self.expression()?;
             self.consume(Types::RIGHT_PAREN, "Expect ')' after expression")?;
             Ok(Expr::Grouping {
+                id: ExprId::fresh(),
                 expression: Box::new(expr),
+                span: self.span_from(start),
+            })
+        } else if self.matches(vec![Types::SUPER]) {
+            let keyword = self.previous().clone();
+            self.consume(Types::DOT, "Expect '.' after 'super'.")?;
+            let method = self
+                .consume(Types::IDENTIFIER, "Expect superclass method name.")?
+                .clone();
+            Ok(Expr::Super {
+                id: ExprId::fresh(),
+                keyword,
+                method,
+            })
+        } else if self.matches(vec![Types::THIS]) {
+            Ok(Expr::This {
+                id: ExprId::fresh(),
+                keyword: self.previous().clone(),
             })
         } else if self.matches(vec![Types::IDENTIFIER]) {
             let token = self.previous().clone();
-            Ok(Expr::Variable { name: token })
+            Ok(Expr::Variable {
+                id: ExprId::fresh(),
+                name: token,
+            })
         } else {
-            let err = LuxError::new(self.peek(), "Expect expression.");
+            let err = LuxError::new(
+                self.peek(),
+                ErrorCode::ExpectedExpression,
+                "Expect expression.",
+            );
             Err(err)
         }
     }
 
+    // "a ${b} c ${d} e" is scanned as INTERPOLATION("a ") b INTERPOLATION(" c ")
+    // d STRING(" e"), and becomes the parts a, b, c, d, e of one expression.
+    fn interpolation(&mut self) -> ParserResult<Expr> {
+        let start = self.previous().span;
+        let mut parts: Vec<Expr> = Vec::new();
+        loop {
+            self.push_string_part(&mut parts);
+            parts.push(self.expression()?);
+            if !self.matches(vec![Types::INTERPOLATION]) {
+                break;
+            }
+        }
+        self.consume(Types::STRING, "Expect '}' after interpolated expression.")?;
+        self.push_string_part(&mut parts);
+
+        Ok(Expr::Interpolation {
+            id: ExprId::fresh(),
+            parts,
+            span: self.span_from(start),
+        })
+    }
+
+    fn push_string_part(&self, parts: &mut Vec<Expr>) {
+        let segment = self.previous();
+        if segment.literal != Literal::String(String::new()) {
+            parts.push(Expr::Literal {
+                id: ExprId::fresh(),
+                value: segment.literal.clone(),
+                span: segment.span,
+            });
+        }
+    }
+
+    // A '{' in statement position starts a block unless a ':' follows the
+    // first key, in which case it is a map literal used as an expression.
+    // Brackets are skipped over without being parsed, and a ';' or the '}'
+    // closing the braces means a block.
+    fn is_map_literal(&self) -> bool {
+        if !self.check(Types::LEFT_BRACE) {
+            return false;
+        }
+
+        let mut depth = 0;
+        for token in &self.tokens[self.current + 1..] {
+            match token.type_t {
+                Types::LEFT_PAREN | Types::LEFT_BRACKET | Types::LEFT_BRACE => depth += 1,
+                Types::RIGHT_PAREN | Types::RIGHT_BRACKET | Types::RIGHT_BRACE if depth > 0 => {
+                    depth -= 1
+                }
+                Types::COLON if depth == 0 => return true,
+                Types::RIGHT_PAREN
+                | Types::RIGHT_BRACKET
+                | Types::RIGHT_BRACE
+                | Types::SEMICOLON
+                | Types::EOF
+                    if depth == 0 =>
+                {
+                    return false
+                }
+                _ => {}
+            }
+        }
+        false
+    }
+
+    // Looks past a '(' for a parameter list followed by '=>'.
+    fn is_arrow_lambda(&self) -> bool {
+        if !self.check(Types::LEFT_PAREN) {
+            return false;
+        }
+
+        let mut offset = 1;
+        if !self.check_ahead(offset, Types::RIGHT_PAREN) {
+            loop {
+                if !self.check_ahead(offset, Types::IDENTIFIER) {
+                    return false;
+                }
+                offset += 1;
+                if !self.check_ahead(offset, Types::COMMA) {
+                    break;
+                }
+                offset += 1;
+            }
+        }
+
+        self.check_ahead(offset, Types::RIGHT_PAREN) && self.check_ahead(offset + 1, Types::ARROW)
+    }
+
     fn consume(&mut self, token_type: Types, message: &str) -> ParserResult<&Token> {
         if self.check(token_type) {
-            return Ok(self.advance());
+            Ok(self.advance())
         } else {
             let token = self.peek();
-            let err = self.error(token, message);
+            let err = self.error(token, ErrorCode::UnexpectedToken, message);
             Err(err)
         }
     }
 
-    fn error(&self, token: &Token, message: &str) -> LuxError {
-        LuxError::new(token, message)
+    fn error(&self, token: &Token, code: ErrorCode, message: &str) -> LuxError {
+        LuxError::new(token, code, message)
     }
 
+    // Skips tokens up to the start of the next statement. A '}' closing the
+    // enclosing block is left for `block` to consume.
     fn synchronize(&mut self) {
+        if self.closes_block() {
+            return;
+        }
+
         self.advance();
         while !self.is_at_end() {
-            if self.previous().type_t == Types::SEMICOLON {
+            if self.previous().type_t == Types::SEMICOLON || self.closes_block() {
                 return;
             }
             match self.peek().type_t {
@@ -472,12 +984,22 @@ impl Parser {
                 | Types::IF
                 | Types::WHILE
                 | Types::PRINT
-                | Types::RETURN => return,
+                | Types::RETURN
+                | Types::BREAK
+                | Types::CONTINUE
+                | Types::THROW
+                | Types::TRY
+                | Types::IMPORT
+                | Types::FROM => return,
                 _ => self.advance(),
             };
         }
     }
 
+    fn closes_block(&self) -> bool {
+        self.block_depth > 0 && self.check(Types::RIGHT_BRACE)
+    }
+
     fn matches(&mut self, token_types: Vec<Types>) -> bool {
         for token in token_types {
             if self.check(token) {
@@ -502,6 +1024,12 @@ impl Parser {
         self.peek().type_t == token_type
     }
 
+    fn check_ahead(&self, offset: usize, token_type: Types) -> bool {
+        self.tokens
+            .get(self.current + offset)
+            .is_some_and(|token| token.type_t == token_type)
+    }
+
     fn is_at_end(&self) -> bool {
         self.peek().type_t == Types::EOF
     }
@@ -513,4 +1041,9 @@ impl Parser {
     fn previous(&self) -> &Token {
         &self.tokens[self.current - 1]
     }
+
+    // The span from `start` to the end of the last token consumed.
+    fn span_from(&self, start: Span) -> Span {
+        start.to(self.previous().span)
+    }
 }
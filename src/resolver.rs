@@ -1,38 +1,145 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use crate::{
-    error::LuxError, expr::Expr, function::Function, interpreter::Interpreter, stmt::Stmt,
+    environment::Slot,
+    error::LuxError,
+    error_code::ErrorCode,
+    expr::{Expr, ExprId},
+    function::Function,
+    interpreter::Interpreter,
+    module::ModuleLoader,
+    stmt::Stmt,
+    suggestion::did_you_mean,
     token::Token,
 };
 
 pub struct Resolver<'a> {
-    scopes: VecDeque<HashMap<String, bool>>,
+    scopes: VecDeque<HashMap<String, Variable>>,
     interpreter: &'a mut Interpreter,
     current_function: FunctionType,
+    current_class: ClassType,
+    current_loop: LoopType,
+    warnings: Vec<LuxError>,
+    /// Top-level functions of the script, which nothing outside it can call.
+    /// A module's top-level functions are its API, so they aren't tracked.
+    global_functions: Option<Vec<Token>>,
+    global_uses: HashSet<String>,
 }
 
 type ResolverResult<T> = Result<T, LuxError>;
 
+// A name declared in a local scope.
+struct Variable {
+    name: Token,
+    kind: VariableKind,
+    // Where the value lives in the scope's environment at runtime.
+    slot: usize,
+    defined: bool,
+    used: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VariableKind {
+    Variable,
+    Parameter,
+    Function,
+    Class,
+    // `this` and `super`, which are never reported as unused.
+    Implicit,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum FunctionType {
     None,
     Function,
+    Initializer,
+    Method,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoopType {
+    None,
+    Loop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClassType {
+    None,
+    Class,
+    Subclass,
 }
 
 impl<'a> Resolver<'a> {
     pub fn new(interpreter: &'a mut Interpreter) -> Self {
-        let scopes: VecDeque<HashMap<String, bool>> = VecDeque::new();
+        let scopes: VecDeque<HashMap<String, Variable>> = VecDeque::new();
         let current_function = FunctionType::None;
+        let current_class = ClassType::None;
+        let current_loop = LoopType::None;
         Self {
             scopes,
             interpreter,
             current_function,
+            current_class,
+            current_loop,
+            warnings: vec![],
+            global_functions: Some(vec![]),
+            global_uses: HashSet::new(),
         }
     }
 
+    /// A resolver for an imported module, whose top-level functions may be
+    /// called by the modules importing it.
+    pub fn for_module(interpreter: &'a mut Interpreter) -> Self {
+        Self {
+            global_functions: None,
+            ..Self::new(interpreter)
+        }
+    }
+
+    /// Resolves a whole script or module. Warnings are collected along the way
+    /// and can be read with `warnings`, even when resolving fails.
     pub fn resolve(&mut self, statements: &[Stmt]) -> ResolverResult<()> {
+        self.resolve_statements(statements)?;
+
+        if let Some(functions) = self.global_functions.take() {
+            for name in functions {
+                if !self.global_uses.contains(&name.lexeme) && !name.lexeme.starts_with('_') {
+                    let message = format!("Function '{}' is never called.", name.lexeme);
+                    self.warn_unused(&name, ErrorCode::UnusedFunction, &message);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn warnings(&self) -> &Vec<LuxError> {
+        &self.warnings
+    }
+
+    fn resolve_statements(&mut self, statements: &[Stmt]) -> ResolverResult<()> {
+        let mut exit: Option<&Token> = None;
         for statement in statements {
-            self.resolve_one(statement)?
+            if let Some(keyword) = exit.take() {
+                let mut warning =
+                    LuxError::new(keyword, ErrorCode::Unreachable, "Unreachable code.")
+                        .with_span(statement.span());
+                warning.notes.push(format!(
+                    "'{}' on line {} leaves the block before this runs",
+                    keyword.lexeme, keyword.line
+                ));
+                self.warnings.push(warning);
+            }
+
+            self.resolve_one(statement)?;
+
+            exit = match statement {
+                Stmt::Return { keyword, .. }
+                | Stmt::Break { keyword, .. }
+                | Stmt::Continue { keyword, .. }
+                | Stmt::Throw { keyword, .. } => Some(keyword),
+                _ => None,
+            };
         }
 
         Ok(())
@@ -40,10 +147,12 @@ impl<'a> Resolver<'a> {
 
     fn resolve_one(&mut self, statement: &Stmt) -> ResolverResult<()> {
         match statement {
-            Stmt::Expression { expression } => self.resolve_expr(expression),
-            Stmt::Print { expression } => self.resolve_expr(expression.as_ref()),
-            Stmt::Var { name, initializer } => {
-                self.declare(name)?;
+            Stmt::Expression { expression, .. } => self.resolve_expr(expression),
+            Stmt::Print { expression, .. } => self.resolve_expr(expression.as_ref()),
+            Stmt::Var {
+                name, initializer, ..
+            } => {
+                self.declare(name, VariableKind::Variable)?;
                 if **initializer != Expr::Nil {
                     self.resolve_expr(initializer)?
                 }
@@ -51,9 +160,9 @@ impl<'a> Resolver<'a> {
 
                 Ok(())
             }
-            Stmt::Block { statements } => {
+            Stmt::Block { statements, .. } => {
                 self.begin_scope();
-                self.resolve(statements)?;
+                self.resolve_statements(statements)?;
                 self.end_scope();
 
                 Ok(())
@@ -62,6 +171,7 @@ impl<'a> Resolver<'a> {
                 condition,
                 then_branch,
                 else_branch,
+                ..
             } => {
                 self.resolve_expr(condition)?;
                 self.resolve_one(then_branch)?;
@@ -73,17 +183,176 @@ impl<'a> Resolver<'a> {
                 Ok(())
             }
             Stmt::Function(func) => {
-                self.declare(&func.name)?;
-                self.define(&func.name);
+                let name = func.declared_name();
+                if self.scopes.is_empty() {
+                    if let Some(functions) = &mut self.global_functions {
+                        functions.push(name.clone());
+                    }
+                }
+                self.declare(name, VariableKind::Function)?;
+                self.define(name);
                 self.resolve_func(func, FunctionType::Function)
             }
-            Stmt::While { condition, body } => {
+            Stmt::Class {
+                name,
+                superclass,
+                methods,
+                ..
+            } => {
+                let enclosing_class = self.current_class;
+                self.current_class = ClassType::Class;
+
+                self.declare(name, VariableKind::Class)?;
+                self.define(name);
+
+                if let Some(superclass) = superclass {
+                    if let Expr::Variable {
+                        name: super_name, ..
+                    } = superclass.as_ref()
+                    {
+                        if super_name.lexeme == name.lexeme {
+                            return Err(LuxError::new(
+                                super_name,
+                                ErrorCode::InheritsFromItself,
+                                "A class can't inherit from itself.",
+                            ));
+                        }
+                    }
+
+                    self.current_class = ClassType::Subclass;
+                    self.resolve_expr(superclass)?;
+
+                    self.begin_scope();
+                    self.define_implicit(name, "super");
+                }
+
+                self.begin_scope();
+                self.define_implicit(name, "this");
+
+                for method in methods {
+                    let declaration = if method.declared_name().lexeme == "init" {
+                        FunctionType::Initializer
+                    } else {
+                        FunctionType::Method
+                    };
+                    self.resolve_func(method, declaration)?;
+                }
+
+                self.end_scope();
+
+                if superclass.is_some() {
+                    self.end_scope();
+                }
+
+                self.current_class = enclosing_class;
+
+                Ok(())
+            }
+            Stmt::While {
+                condition,
+                body,
+                increment,
+                ..
+            } => {
+                let enclosing_loop = self.current_loop;
+                self.current_loop = LoopType::Loop;
+
                 self.resolve_expr(condition)?;
-                self.resolve_one(body)
+                self.resolve_one(body)?;
+                if let Some(increment) = increment {
+                    self.resolve_expr(increment)?;
+                }
+
+                self.current_loop = enclosing_loop;
+
+                Ok(())
             }
-            Stmt::Return { value, keyword } => {
+            Stmt::Import {
+                path, alias, names, ..
+            } => {
+                if !self.scopes.is_empty() {
+                    return Err(LuxError::new(
+                        path,
+                        ErrorCode::ImportOutsideTopLevel,
+                        "Can't import outside of the top level of a module.",
+                    ));
+                }
+
+                ModuleLoader::prepare(self.interpreter, path)?;
+                self.warnings
+                    .append(&mut self.interpreter.modules.take_warnings());
+
+                for name in alias.iter().chain(names) {
+                    self.declare(name, VariableKind::Variable)?;
+                    self.define(name);
+                }
+
+                Ok(())
+            }
+            Stmt::Throw { value, .. } => self.resolve_expr(value),
+            Stmt::Try {
+                body,
+                catch,
+                finally,
+                ..
+            } => {
+                self.begin_scope();
+                self.resolve_statements(body)?;
+                self.end_scope();
+
+                if let Some((name, catch_body)) = catch {
+                    self.begin_scope();
+                    self.declare(name, VariableKind::Variable)?;
+                    self.define(name);
+                    self.resolve_statements(catch_body)?;
+                    self.end_scope();
+                }
+
+                if let Some(finally_body) = finally {
+                    self.begin_scope();
+                    self.resolve_statements(finally_body)?;
+                    self.end_scope();
+                }
+
+                Ok(())
+            }
+            Stmt::Break { keyword, .. } => {
+                if self.current_loop == LoopType::None {
+                    return Err(LuxError::new(
+                        keyword,
+                        ErrorCode::OutsideLoop,
+                        "Can't use 'break' outside of a loop.",
+                    ));
+                }
+
+                Ok(())
+            }
+            Stmt::Continue { keyword, .. } => {
+                if self.current_loop == LoopType::None {
+                    return Err(LuxError::new(
+                        keyword,
+                        ErrorCode::OutsideLoop,
+                        "Can't use 'continue' outside of a loop.",
+                    ));
+                }
+
+                Ok(())
+            }
+            Stmt::Return { value, keyword, .. } => {
                 if self.current_function == FunctionType::None {
-                    return Err(LuxError::new(keyword, "Can't return from top-level code."));
+                    return Err(LuxError::new(
+                        keyword,
+                        ErrorCode::InvalidReturn,
+                        "Can't return from top-level code.",
+                    ));
+                }
+
+                if **value != Expr::Nil && self.current_function == FunctionType::Initializer {
+                    return Err(LuxError::new(
+                        keyword,
+                        ErrorCode::InvalidReturn,
+                        "Can't return a value from an initializer.",
+                    ));
                 }
 
                 self.resolve_expr(value.as_ref())
@@ -98,23 +367,26 @@ impl<'a> Resolver<'a> {
     ) -> ResolverResult<()> {
         let enclosing_function = self.current_function;
         self.current_function = function_type;
+        let enclosing_loop = self.current_loop;
+        self.current_loop = LoopType::None;
 
         self.begin_scope();
 
         for param in &function.param {
-            self.declare(param)?;
+            self.declare(param, VariableKind::Parameter)?;
             self.define(param);
         }
 
-        self.resolve(&function.body)?;
+        self.resolve_statements(&function.body)?;
         self.end_scope();
 
         self.current_function = enclosing_function;
+        self.current_loop = enclosing_loop;
 
         Ok(())
     }
 
-    fn declare(&mut self, name: &Token) -> ResolverResult<()> {
+    fn declare(&mut self, name: &Token, kind: VariableKind) -> ResolverResult<()> {
         if self.scopes.is_empty() {
             return Ok(());
         }
@@ -124,12 +396,46 @@ impl<'a> Resolver<'a> {
         if scope.unwrap().contains_key(&name.lexeme) {
             return Err(LuxError::new(
                 name,
+                ErrorCode::AlreadyDeclared,
                 "Already a variable with this name in this scope.",
             ));
         }
 
+        let shadowed = self
+            .scopes
+            .iter()
+            .rev()
+            .skip(1)
+            .find_map(|scope| scope.get(&name.lexeme))
+            .filter(|outer| outer.kind != VariableKind::Implicit)
+            .map(|outer| outer.name.line);
+        if let Some(line) = shadowed {
+            let mut warning = LuxError::new(
+                name,
+                ErrorCode::Shadowing,
+                &format!(
+                    "Declaration of '{}' shadows a local variable from an enclosing scope.",
+                    name.lexeme
+                ),
+            );
+            warning.notes.push(format!(
+                "the shadowed variable is declared on line {}",
+                line
+            ));
+            self.warnings.push(warning);
+        }
+
         if let Some(map) = self.scopes.back_mut() {
-            map.insert(name.lexeme.clone(), false);
+            map.insert(
+                name.lexeme.clone(),
+                Variable {
+                    name: name.clone(),
+                    kind,
+                    slot: map.len(),
+                    defined: false,
+                    used: false,
+                },
+            );
         }
 
         Ok(())
@@ -142,22 +448,66 @@ impl<'a> Resolver<'a> {
 
         let scope = self.scopes.back_mut();
 
-        if let Some(map) = scope {
-            map.insert(name.lexeme.clone(), true);
+        if let Some(variable) = scope.and_then(|map| map.get_mut(&name.lexeme)) {
+            variable.defined = true;
         }
     }
 
-    fn resolve_local(&mut self, expr: &Expr, name: Token) {
-        let mut i = (self.scopes.len() as i64) - 1;
+    // Declares `this` or `super` in the innermost scope, pointing at `class`.
+    fn define_implicit(&mut self, class: &Token, name: &str) {
+        if let Some(scope) = self.scopes.back_mut() {
+            scope.insert(
+                name.to_string(),
+                Variable {
+                    name: class.clone(),
+                    kind: VariableKind::Implicit,
+                    slot: scope.len(),
+                    defined: true,
+                    used: true,
+                },
+            );
+        }
+    }
 
-        while i >= 0 {
-            let scope = self.scopes.get(i as usize).unwrap();
-            if scope.contains_key(&name.lexeme) {
-                self.interpreter
-                    .resolve(expr, self.scopes.len() - 1 - i as usize)
+    // Resolves `name` to the innermost scope declaring it, if any, and marks
+    // it used when `read` is set. Anything else is a global, which gets a
+    // hint from the locals in scope in case there is no such global.
+    fn resolve_local(&mut self, id: ExprId, name: Token, read: bool) {
+        let depth = self.scopes.len();
+        for (i, scope) in self.scopes.iter_mut().enumerate().rev() {
+            if let Some(variable) = scope.get_mut(&name.lexeme) {
+                variable.used |= read;
+                let slot = Slot {
+                    depth: depth - 1 - i,
+                    index: variable.slot,
+                };
+                self.interpreter.resolve(id, slot);
+                return;
             }
-            i -= 1;
         }
+
+        let locals = self
+            .scopes
+            .iter()
+            .flat_map(|scope| scope.values())
+            .filter(|variable| variable.kind != VariableKind::Implicit)
+            .map(|variable| variable.name.lexeme.as_str());
+        if let Some(help) = did_you_mean(&name.lexeme, locals) {
+            self.interpreter.hint(id, help);
+        }
+
+        if read {
+            self.global_uses.insert(name.lexeme);
+        }
+    }
+
+    fn warn_unused(&mut self, name: &Token, code: ErrorCode, message: &str) {
+        let mut warning = LuxError::new(name, code, message);
+        warning.notes.push(format!(
+            "if this is intentional, prefix it with an underscore: '_{}'",
+            name.lexeme
+        ));
+        self.warnings.push(warning);
     }
 
     fn resolve_expr(&mut self, expression: &Expr) -> ResolverResult<()> {
@@ -166,27 +516,28 @@ impl<'a> Resolver<'a> {
                 self.resolve_expr(left)?;
                 self.resolve_expr(right)
             }
-            Expr::Grouping { expression } => self.resolve_expr(expression),
+            Expr::Grouping { expression, .. } => self.resolve_expr(expression),
             Expr::Literal { .. } => Ok(()),
             Expr::Unary { right, .. } => self.resolve_expr(right),
-            Expr::Variable { name } => {
+            Expr::Variable { id, name } => {
                 if !self.scopes.is_empty() {
                     let value = self.scopes.back().unwrap().get(&name.lexeme);
-                    if let Some(val) = value {
-                        if !(*val) {
+                    if let Some(variable) = value {
+                        if !variable.defined {
                             return Err(LuxError::new(
                                 name,
+                                ErrorCode::ReadInOwnInitializer,
                                 "Can't read local variable in its own initializer.",
                             ));
                         }
                     }
                 }
-                self.resolve_local(&expression, name.clone());
+                self.resolve_local(*id, name.clone(), true);
                 Ok(())
             }
-            Expr::Assign { name, value } => {
+            Expr::Assign { id, name, value } => {
                 self.resolve_expr(value)?;
-                self.resolve_local(&expression, name.clone());
+                self.resolve_local(*id, name.clone(), false);
                 Ok(())
             }
             Expr::Logical { left, right, .. } => {
@@ -202,16 +553,115 @@ impl<'a> Resolver<'a> {
                 }
                 Ok(())
             }
+            Expr::Get { object, .. } => self.resolve_expr(object),
+            Expr::Set { object, value, .. } => {
+                self.resolve_expr(value)?;
+                self.resolve_expr(object)
+            }
+            Expr::This { id, keyword } => {
+                if self.current_class == ClassType::None {
+                    return Err(LuxError::new(
+                        keyword,
+                        ErrorCode::NoClass,
+                        "Can't use 'this' outside of a class.",
+                    ));
+                }
+
+                self.resolve_local(*id, keyword.clone(), true);
+                Ok(())
+            }
+            Expr::Super { id, keyword, .. } => {
+                if self.current_class == ClassType::None {
+                    return Err(LuxError::new(
+                        keyword,
+                        ErrorCode::NoClass,
+                        "Can't use 'super' outside of a class.",
+                    ));
+                } else if self.current_class != ClassType::Subclass {
+                    return Err(LuxError::new(
+                        keyword,
+                        ErrorCode::NoClass,
+                        "Can't use 'super' in a class with no superclass.",
+                    ));
+                }
+
+                self.resolve_local(*id, keyword.clone(), true);
+                Ok(())
+            }
+            Expr::List { elements, .. } => {
+                for element in elements {
+                    self.resolve_expr(element)?;
+                }
+                Ok(())
+            }
+            Expr::Interpolation { parts, .. } => {
+                for part in parts {
+                    self.resolve_expr(part)?;
+                }
+                Ok(())
+            }
+            Expr::Map { entries, .. } => {
+                for (key, value) in entries {
+                    self.resolve_expr(key)?;
+                    self.resolve_expr(value)?;
+                }
+                Ok(())
+            }
+            Expr::Index { object, index, .. } => {
+                self.resolve_expr(object)?;
+                self.resolve_expr(index)
+            }
+            Expr::SetIndex {
+                object,
+                index,
+                value,
+                ..
+            } => {
+                self.resolve_expr(value)?;
+                self.resolve_expr(object)?;
+                self.resolve_expr(index)
+            }
+            Expr::Lambda { function, .. } => self.resolve_func(function, FunctionType::Function),
             Expr::Nil => Ok(()),
         }
     }
 
     fn begin_scope(&mut self) {
-        let scope: HashMap<String, bool> = HashMap::new();
+        let scope: HashMap<String, Variable> = HashMap::new();
         self.scopes.push_back(scope)
     }
 
+    // Warns about the scope's variables that were never read. A leading
+    // underscore marks a name as unused on purpose.
     fn end_scope(&mut self) {
-        self.scopes.pop_back();
+        let scope = match self.scopes.pop_back() {
+            Some(scope) => scope,
+            None => return,
+        };
+
+        let mut unused: Vec<Variable> = scope
+            .into_values()
+            .filter(|variable| !variable.used && !variable.name.lexeme.starts_with('_'))
+            .collect();
+        unused.sort_by_key(|variable| variable.name.span.start);
+        for variable in unused {
+            let name = &variable.name.lexeme;
+            let (code, message) = match variable.kind {
+                VariableKind::Variable => (
+                    ErrorCode::UnusedVariable,
+                    format!("Unused variable '{}'.", name),
+                ),
+                VariableKind::Parameter => (
+                    ErrorCode::UnusedParameter,
+                    format!("Unused parameter '{}'.", name),
+                ),
+                VariableKind::Function => (
+                    ErrorCode::UnusedFunction,
+                    format!("Function '{}' is never called.", name),
+                ),
+                VariableKind::Class | VariableKind::Implicit => continue,
+            };
+            self.warn_unused(&variable.name, code, &message);
+        }
     }
 }
@@ -1,16 +1,100 @@
-use std::fmt;
+use std::{fmt, path::Path, rc::Rc};
 
-use crate::token::Token;
+use crate::{
+    error_code::ErrorCode,
+    interpreter::RuntimeResult,
+    literal::{Float, Literal},
+    span::Span,
+    token::Token,
+};
 
 #[derive(Debug)]
 pub struct RuntimeError {
-    pub token: Token,
+    // Boxed, like the thrown value, to keep `RuntimeResult` small.
+    pub token: Box<Token>,
+    pub code: ErrorCode,
     pub message: String,
+    pub value: Option<Box<Literal>>,
+    /// The source range the error is about: the token's own span unless the
+    /// whole expression around it is to blame.
+    pub span: Span,
+    /// The file the error was raised in, filled in as it leaves the function
+    /// or module it was raised in.
+    pub file: Option<Rc<Path>>,
+    /// The calls in progress when the error was raised, innermost first.
+    pub trace: Vec<TraceLine>,
+    /// A hint at how to fix the error, such as a name that was probably meant.
+    /// A boxed `str` rather than a `String`, to keep `RuntimeResult` small.
+    pub help: Option<Box<str>>,
+}
+
+/// A line of a stack trace: the function running and the line it was on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceLine {
+    pub function: String,
+    pub file: Rc<Path>,
+    pub line: usize,
 }
 
 impl RuntimeError {
-    pub fn new(token: Token, message: String) -> RuntimeError {
-        RuntimeError { token, message }
+    pub fn new(token: Token, code: ErrorCode, message: String) -> RuntimeError {
+        RuntimeError {
+            span: token.span,
+            token: Box::new(token),
+            code,
+            message,
+            value: None,
+            file: None,
+            trace: vec![],
+            help: None,
+        }
+    }
+
+    pub fn with_span(mut self, span: Span) -> RuntimeError {
+        self.span = span;
+        self
+    }
+
+    pub fn with_help(mut self, help: Option<String>) -> RuntimeError {
+        self.help = help.map(String::into_boxed_str);
+        self
+    }
+
+    pub fn in_file(mut self, file: &Rc<Path>) -> RuntimeError {
+        if self.file.is_none() {
+            self.file = Some(file.clone());
+        }
+        self
+    }
+
+    /// An error raised by a `throw` statement, carrying the thrown value.
+    pub fn thrown(token: Token, value: Literal) -> RuntimeError {
+        let message = match &value {
+            Literal::Error(error) => error.message.clone(),
+            _ => value.to_string(),
+        };
+        RuntimeError {
+            span: token.span,
+            token: Box::new(token),
+            code: ErrorCode::Uncaught,
+            message,
+            value: Some(Box::new(value)),
+            file: None,
+            trace: vec![],
+            help: None,
+        }
+    }
+
+    /// The value a `catch` clause binds: whatever was thrown, or an error
+    /// value describing an error raised by the interpreter itself.
+    pub fn into_value(self) -> Literal {
+        match self.value {
+            Some(value) => *value,
+            None => Literal::Error(Box::new(ErrorValue {
+                message: self.message,
+                line: self.token.line,
+            })),
+        }
     }
 }
 
@@ -19,11 +103,33 @@ impl fmt::Display for RuntimeError {
         write!(
             f,
             "{} {} \n [line {}]",
-            self.token.to_string(),
-            self.message,
-            self.token.line
+            self.token, self.message, self.token.line
         )
     }
 }
 
 impl std::error::Error for RuntimeError {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ErrorValue {
+    pub message: String,
+    pub line: usize,
+}
+
+impl ErrorValue {
+    pub fn get(&self, name: &Token) -> RuntimeResult<Literal> {
+        match name.lexeme.as_str() {
+            "message" => Ok(Literal::String(self.message.clone())),
+            "line" => Ok(Literal::Number(Float(self.line as f64))),
+            _ => Err(RuntimeError::new(
+                name.to_owned(),
+                ErrorCode::UndefinedMember,
+                format!("Undefined property '{}'.", name.lexeme),
+            )),
+        }
+    }
+
+    pub fn to_str(&self) -> String {
+        format!("Error: {}", self.message)
+    }
+}
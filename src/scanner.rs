@@ -1,7 +1,9 @@
 use std::collections::HashMap;
 
 use crate::error::LuxError;
+use crate::error_code::ErrorCode;
 use crate::literal::{Float, Literal};
+use crate::span::Span;
 use crate::token::Token;
 use crate::token_type::Types;
 
@@ -11,7 +13,21 @@ pub struct Scanner {
     start: usize,
     current: usize,
     line: usize,
+    column: usize,
+    // Where the lexeme being scanned starts.
+    start_line: usize,
+    start_column: usize,
     keywords: HashMap<String, Types>,
+    // The `${` interpolations currently open, innermost last.
+    interpolations: Vec<Interpolation>,
+    errors: Vec<LuxError>,
+}
+
+// The '}' closing an interpolation is the one met at brace depth zero, and
+// resumes the string it was opened in.
+struct Interpolation {
+    depth: usize,
+    triple: bool,
 }
 
 impl Scanner {
@@ -22,23 +38,41 @@ impl Scanner {
             start: 0,
             current: 0,
             line: 1,
+            column: 1,
+            start_line: 1,
+            start_column: 1,
             keywords: HashMap::new(),
+            interpolations: Vec::new(),
+            errors: Vec::new(),
         };
 
         scanner.keywords.insert("and".to_string(), Types::AND);
+        scanner.keywords.insert("as".to_string(), Types::AS);
+        scanner.keywords.insert("break".to_string(), Types::BREAK);
+        scanner.keywords.insert("catch".to_string(), Types::CATCH);
         scanner.keywords.insert("class".to_string(), Types::CLASS);
+        scanner
+            .keywords
+            .insert("continue".to_string(), Types::CONTINUE);
         scanner.keywords.insert("else".to_string(), Types::ELSE);
         scanner.keywords.insert("false".to_string(), Types::FALSE);
+        scanner
+            .keywords
+            .insert("finally".to_string(), Types::FINALLY);
         scanner.keywords.insert("for".to_string(), Types::FOR);
+        scanner.keywords.insert("from".to_string(), Types::FROM);
         scanner.keywords.insert("fun".to_string(), Types::FUN);
         scanner.keywords.insert("if".to_string(), Types::IF);
+        scanner.keywords.insert("import".to_string(), Types::IMPORT);
         scanner.keywords.insert("nil".to_string(), Types::NIL);
         scanner.keywords.insert("or".to_string(), Types::OR);
         scanner.keywords.insert("print".to_string(), Types::PRINT);
         scanner.keywords.insert("return".to_string(), Types::RETURN);
         scanner.keywords.insert("super".to_string(), Types::SUPER);
         scanner.keywords.insert("this".to_string(), Types::THIS);
+        scanner.keywords.insert("throw".to_string(), Types::THROW);
         scanner.keywords.insert("true".to_string(), Types::TRUE);
+        scanner.keywords.insert("try".to_string(), Types::TRY);
         scanner.keywords.insert("var".to_string(), Types::VAR);
         scanner.keywords.insert("while".to_string(), Types::WHILE);
 
@@ -49,13 +83,31 @@ impl Scanner {
         while !self.is_at_end() {
             // We are at the beginning of the next lexeme.
             self.start = self.current;
-            self.scan_token().unwrap();
+            self.start_line = self.line;
+            self.start_column = self.column;
+            if let Err(err) = self.scan_token() {
+                self.errors.push(err);
+                self.add_token(Types::ERROR);
+            }
         }
-        let token = Token::new(Types::EOF, "".to_string(), Literal::Nil, self.line);
+        let token = Token::new(
+            Types::EOF,
+            "".to_string(),
+            Literal::Nil,
+            self.line,
+            self.column,
+            Span::new(self.current, self.current),
+        );
         self.tokens.push(token);
         &self.tokens
     }
 
+    /// The errors met by `scan_tokens`, whose offending characters are left out
+    /// of the tokens.
+    pub fn errors(&self) -> &Vec<LuxError> {
+        &self.errors
+    }
+
     fn is_at_end(&self) -> bool {
         self.current >= self.source.len()
     }
@@ -72,17 +124,40 @@ impl Scanner {
                 Ok(())
             }
             '{' => {
+                if let Some(interpolation) = self.interpolations.last_mut() {
+                    interpolation.depth += 1;
+                }
                 self.add_token(Types::LEFT_BRACE);
                 Ok(())
             }
             '}' => {
+                if let Some(interpolation) = self.interpolations.last_mut() {
+                    if interpolation.depth == 0 {
+                        let triple = interpolation.triple;
+                        self.interpolations.pop();
+                        return self.string(triple);
+                    }
+                    interpolation.depth -= 1;
+                }
                 self.add_token(Types::RIGHT_BRACE);
                 Ok(())
             }
+            '[' => {
+                self.add_token(Types::LEFT_BRACKET);
+                Ok(())
+            }
+            ']' => {
+                self.add_token(Types::RIGHT_BRACKET);
+                Ok(())
+            }
             ',' => {
                 self.add_token(Types::COMMA);
                 Ok(())
             }
+            ':' => {
+                self.add_token(Types::COLON);
+                Ok(())
+            }
             '.' => {
                 self.add_token(Types::DOT);
                 Ok(())
@@ -117,6 +192,8 @@ impl Scanner {
             '=' => {
                 let token_to_add = if self.matches_char('=') {
                     Types::EQUAL_EQUAL
+                } else if self.matches_char('>') {
+                    Types::ARROW
                 } else {
                     Types::EQUAL
                 };
@@ -158,17 +235,15 @@ impl Scanner {
             ' ' => Ok(()),
             '\r' => Ok(()),
             '\t' => Ok(()),
-
-            '\n' => {
-                self.line += 1;
-                Ok(())
-            }
+            '\n' => Ok(()),
 
             '"' => {
-                self.string().unwrap();
-                Ok(())
+                let triple = self.open_quote();
+                self.string(triple)
             }
 
+            'r' if self.peek() == '"' => self.raw_string(),
+
             ident => {
                 if Scanner::is_digit(ident) {
                     self.number();
@@ -177,24 +252,31 @@ impl Scanner {
                     self.identifier();
                     Ok(())
                 } else {
-                    Err(LuxError {
-                        line: self.line,
-                        location: "".to_string(),
-                        message: "Unexpected character".to_string(),
-                    })
+                    Err(self.error(
+                        ErrorCode::UnexpectedCharacter,
+                        &format!("Unexpected character '{}'.", ident),
+                    ))
                 }
             }
         }
     }
 
+    // `start` and `current` are byte offsets into `source`, so that lexemes can
+    // be sliced out of it even when it holds multi-byte characters. Lines and
+    // columns are counted here, as every character is consumed through here.
     pub fn advance(&mut self) -> char {
-        let next_char = &self
-            .source
+        let next_char = self.source[self.current..]
             .chars()
-            .nth(self.current)
+            .next()
             .expect("Failed to read char from advance method");
-        self.current += 1;
-        next_char.to_owned()
+        self.current += next_char.len_utf8();
+        if next_char == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        next_char
     }
 
     pub fn add_token(&mut self, token_type: Types) {
@@ -203,49 +285,170 @@ impl Scanner {
 
     fn add_token_literal(&mut self, token_type: Types, literal: Literal) {
         let text = &self.source[self.start..self.current];
-        self.tokens
-            .push(Token::new(token_type, text.to_string(), literal, self.line))
+        self.tokens.push(Token::new(
+            token_type,
+            text.to_string(),
+            literal,
+            self.start_line,
+            self.start_column,
+            Span::new(self.start, self.current),
+        ))
     }
 
     fn matches_char(&mut self, expected: char) -> bool {
-        if self.is_at_end() || self.source.chars().nth(self.current).unwrap() != expected {
+        if self.is_at_end() || self.peek() != expected {
             false
         } else {
-            self.current += 1;
+            self.advance();
             true
         }
     }
 
-    fn peek(&mut self) -> char {
-        if self.is_at_end() {
-            '\0'
-        } else {
-            self.source.chars().nth(self.current).unwrap()
-        }
+    fn peek(&self) -> char {
+        self.peek_at(0)
     }
 
-    fn string(&mut self) -> Result<(), LuxError> {
-        while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.line += 1;
+    // Scans the rest of a string starting after its opening quote, or after
+    // the '}' closing an interpolation.
+    fn string(&mut self, triple: bool) -> Result<(), LuxError> {
+        let mut value = String::new();
+        while !self.is_at_closing_quote(triple) {
+            if self.is_at_end() {
+                return Err(self.error(ErrorCode::UnterminatedString, "Unterminated string"));
             }
-            self.advance();
-        }
 
-        if self.is_at_end() {
-            return Err(LuxError {
-                line: self.line,
-                location: "".to_string(),
-                message: "Unterminated string".to_string(),
-            });
+            match self.advance() {
+                '\\' if !self.is_at_end() => {
+                    // A bad escape doesn't end the string, so scanning
+                    // carries on inside it.
+                    match self.escape() {
+                        Ok(escaped) => value.push(escaped),
+                        Err(err) => self.errors.push(err),
+                    }
+                }
+                '$' if self.peek() == '{' => {
+                    self.advance();
+                    self.add_token_literal(Types::INTERPOLATION, Literal::String(value));
+                    self.interpolations.push(Interpolation { depth: 0, triple });
+                    return Ok(());
+                }
+                c => value.push(c),
+            }
         }
 
+        self.close_quote(triple);
+        self.add_token_literal(Types::STRING, Literal::String(value));
+        Ok(())
+    }
+
+    // Raw strings (`r"..."`) keep backslashes and `${` as written.
+    fn raw_string(&mut self) -> Result<(), LuxError> {
         self.advance();
-        let value = (&self.source[self.start + 1..self.current - 1]).to_string();
+        let triple = self.open_quote();
+        let mut value = String::new();
+        while !self.is_at_closing_quote(triple) {
+            if self.is_at_end() {
+                return Err(self.error(ErrorCode::UnterminatedString, "Unterminated string"));
+            }
+
+            let c = self.advance();
+            value.push(c);
+        }
+
+        self.close_quote(triple);
         self.add_token_literal(Types::STRING, Literal::String(value));
         Ok(())
     }
 
+    // Called after a string's first quote to consume the other two of a
+    // triple-quoted string, returning whether there were any.
+    fn open_quote(&mut self) -> bool {
+        if self.peek() == '"' && self.peek_next() == '"' {
+            self.advance();
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn is_at_closing_quote(&self, triple: bool) -> bool {
+        if triple {
+            self.peek() == '"' && self.peek_next() == '"' && self.peek_at(2) == '"'
+        } else {
+            self.peek() == '"'
+        }
+    }
+
+    fn close_quote(&mut self, triple: bool) {
+        let quotes = if triple { 3 } else { 1 };
+        for _ in 0..quotes {
+            self.advance();
+        }
+    }
+
+    // Scans the escape sequence following a backslash. Errors point at the
+    // escape rather than the whole string.
+    fn escape(&mut self) -> Result<char, LuxError> {
+        let (start, line, column) = (self.current - 1, self.line, self.column - 1);
+        self.escaped_char().map_err(|message| LuxError {
+            line,
+            column,
+            span: Span::new(start, self.current),
+            ..self.error(ErrorCode::InvalidEscape, &message)
+        })
+    }
+
+    fn escaped_char(&mut self) -> Result<char, String> {
+        match self.advance() {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '"' => Ok('"'),
+            '\\' => Ok('\\'),
+            '$' => Ok('$'),
+            'u' => self.unicode_escape(),
+            c => Err(format!("Invalid escape sequence '\\{}'.", c)),
+        }
+    }
+
+    // Scans the `{XXXX}` part of a `\u{XXXX}` escape.
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        if !self.matches_char('{') {
+            return Err("Expect '{' after '\\u'.".to_string());
+        }
+
+        let mut digits = String::new();
+        while self.peek().is_ascii_hexdigit() {
+            digits.push(self.advance());
+        }
+
+        if !self.matches_char('}') {
+            return Err("Expect '}' after unicode escape digits.".to_string());
+        }
+
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .filter(|_| (1..=6).contains(&digits.len()))
+            .and_then(char::from_u32)
+            .ok_or_else(|| format!("Invalid unicode escape '\\u{{{}}}'.", digits))
+    }
+
+    // Errors cover the lexeme scanned so far.
+    fn error(&self, code: ErrorCode, message: &str) -> LuxError {
+        LuxError {
+            code,
+            line: self.start_line,
+            column: self.start_column,
+            span: Span::new(self.start, self.current),
+            location: "".into(),
+            message: message.to_string(),
+            file: None,
+            notes: vec![],
+        }
+    }
+
     fn number(&mut self) {
         while Scanner::is_digit(self.peek()) {
             self.advance();
@@ -257,17 +460,22 @@ impl Scanner {
             }
         }
 
-        let num = (&self.source[self.start..self.current]).parse().unwrap();
+        let num = self.source[self.start..self.current].parse().unwrap();
 
         self.add_token_literal(Types::NUMBER, Literal::Number(Float(num)))
     }
 
-    fn peek_next(&mut self) -> char {
-        if self.current + 1 >= self.source.len() {
-            '\0'
-        } else {
-            self.source.chars().nth(self.current + 1).unwrap()
-        }
+    fn peek_next(&self) -> char {
+        self.peek_at(1)
+    }
+
+    // The character `offset` bytes ahead. Callers only look past characters
+    // they have already seen are ASCII, so this lands on a character boundary.
+    fn peek_at(&self, offset: usize) -> char {
+        self.source
+            .get(self.current + offset..)
+            .and_then(|rest| rest.chars().next())
+            .unwrap_or('\0')
     }
 
     fn identifier(&mut self) {
@@ -286,12 +494,14 @@ impl Scanner {
         self.add_token(token_type);
     }
 
+    // Identifiers may use letters from any script, but digits stay ASCII so
+    // that numbers scan the same everywhere.
     fn is_alpha(char: char) -> bool {
-        ('a'..='z').contains(&char) || ('A'..='Z').contains(&char) || char == '_'
+        char.is_alphabetic() || char == '_'
     }
 
     fn is_digit(char: char) -> bool {
-        ('0'..='9').contains(&char)
+        char.is_ascii_digit()
     }
 
     fn is_alphanumeric(char: char) -> bool {
@@ -1,32 +1,95 @@
-use crate::{expr::Expr, function::Function, token::Token};
+use crate::{expr::Expr, function::Function, span::Span, token::Token};
 
-#[derive(PartialEq, Clone, Debug, Eq)]
+#[derive(PartialEq, Clone, Debug, Eq, Hash)]
 pub enum Stmt {
     Expression {
         expression: Box<Expr>,
+        span: Span,
     },
     Print {
         expression: Box<Expr>,
+        span: Span,
     },
     Var {
         name: Token,
         initializer: Box<Expr>,
+        span: Span,
     },
     Block {
         statements: Vec<Stmt>,
+        span: Span,
     },
     If {
         condition: Box<Expr>,
         then_branch: Box<Stmt>,
         else_branch: Option<Box<Stmt>>,
+        span: Span,
     },
     Function(Function),
+    Class {
+        name: Token,
+        superclass: Option<Box<Expr>>,
+        methods: Vec<Function>,
+        span: Span,
+    },
     While {
         condition: Box<Expr>,
         body: Box<Stmt>,
+        increment: Option<Box<Expr>>,
+        span: Span,
+    },
+    Break {
+        keyword: Token,
+        span: Span,
+    },
+    Continue {
+        keyword: Token,
+        span: Span,
+    },
+    Throw {
+        keyword: Token,
+        value: Box<Expr>,
+        span: Span,
+    },
+    Import {
+        path: Token,
+        alias: Option<Token>,
+        names: Vec<Token>,
+        span: Span,
+    },
+    Try {
+        body: Vec<Stmt>,
+        catch: Option<(Token, Vec<Stmt>)>,
+        finally: Option<Vec<Stmt>>,
+        span: Span,
     },
     Return {
         keyword: Token,
         value: Box<Expr>,
+        span: Span,
     },
 }
+
+impl Stmt {
+    /// The source range the statement was parsed from, including its
+    /// terminating ';' or '}'. Statements desugared from a `for` loop all
+    /// cover the whole loop.
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::Function(function) => function.span,
+            Stmt::Expression { span, .. }
+            | Stmt::Print { span, .. }
+            | Stmt::Var { span, .. }
+            | Stmt::Block { span, .. }
+            | Stmt::If { span, .. }
+            | Stmt::Class { span, .. }
+            | Stmt::While { span, .. }
+            | Stmt::Break { span, .. }
+            | Stmt::Continue { span, .. }
+            | Stmt::Throw { span, .. }
+            | Stmt::Import { span, .. }
+            | Stmt::Try { span, .. }
+            | Stmt::Return { span, .. } => *span,
+        }
+    }
+}
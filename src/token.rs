@@ -1,6 +1,6 @@
 use std::fmt;
 
-use crate::{literal::Literal, token_type::Types};
+use crate::{literal::Literal, span::Span, token_type::Types};
 
 #[derive(Clone, PartialEq, Eq, Debug, Hash)]
 pub struct Token {
@@ -8,15 +8,27 @@ pub struct Token {
     pub lexeme: String,
     pub literal: Literal,
     pub line: usize,
+    /// Column of the first character, counted in characters from 1.
+    pub column: usize,
+    pub span: Span,
 }
 
 impl Token {
-    pub fn new(type_t: Types, lexeme: String, literal: Literal, line: usize) -> Token {
+    pub fn new(
+        type_t: Types,
+        lexeme: String,
+        literal: Literal,
+        line: usize,
+        column: usize,
+        span: Span,
+    ) -> Token {
         Token {
             type_t,
             lexeme,
             literal,
             line,
+            column,
+            span,
         }
     }
 }
@@ -25,8 +37,8 @@ impl fmt::Display for Token {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         write!(
             f,
-            "Line: {}, Lexeme: {}, Literal: {}, Type: {:?}",
-            self.line, self.lexeme, self.literal, self.type_t
+            "Line: {}, Column: {}, Lexeme: {}, Literal: {}, Type: {:?}",
+            self.line, self.column, self.lexeme, self.literal, self.type_t
         )
     }
 }
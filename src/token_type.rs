@@ -7,7 +7,10 @@ pub enum Types {
     RIGHT_PAREN,
     LEFT_BRACE,
     RIGHT_BRACE,
+    LEFT_BRACKET,
+    RIGHT_BRACKET,
     COMMA,
+    COLON,
     DOT,
     MINUS,
     PLUS,
@@ -20,6 +23,7 @@ pub enum Types {
     BANG_EQUAL,
     EQUAL,
     EQUAL_EQUAL,
+    ARROW,
     GREATER,
     GREATER_EQUAL,
     LESS,
@@ -28,25 +32,39 @@ pub enum Types {
     // Literals.
     IDENTIFIER,
     STRING,
+    // A string segment that ends where a `${` interpolation starts.
+    INTERPOLATION,
     NUMBER,
 
     // Keywords.
     AND,
+    AS,
+    BREAK,
+    CATCH,
     CLASS,
+    CONTINUE,
     ELSE,
     FALSE,
+    FINALLY,
     FUN,
     FOR,
+    FROM,
     IF,
+    IMPORT,
     NIL,
     OR,
     PRINT,
     RETURN,
     SUPER,
     THIS,
+    THROW,
     TRUE,
+    TRY,
     VAR,
     WHILE,
 
+    /// Stands in for text the scanner couldn't scan, which it has already
+    /// reported.
+    ERROR,
     EOF,
 }
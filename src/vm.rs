@@ -0,0 +1,777 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    callable::LuxCallable,
+    chunk::{Location, OpCode},
+    compiler::Compiler,
+    environment::Environment,
+    error_code::ErrorCode,
+    interpreter::{Interpreter, RuntimeResult, MAX_CALL_DEPTH},
+    list::LuxList,
+    literal::{Float, Literal},
+    map::LuxMap,
+    module::{self, ModuleLoader},
+    object::{BoundMethod, Closure, FunctionKind, FunctionProto, Upvalue, VmClass, VmInstance},
+    runtime_error::{RuntimeError, TraceLine},
+};
+
+/// A call in progress: the closure running, the offset of its next
+/// instruction, and the stack slot its slot 0 is in.
+struct Frame {
+    closure: Rc<Closure>,
+    ip: usize,
+    base: usize,
+}
+
+/// Where to go when an error is raised inside a try block.
+struct Handler {
+    frame: usize,
+    ip: usize,
+    /// The height of the stack when the handler was installed.
+    stack: usize,
+    caught: usize,
+    /// Whether the handler is a catch block, which gets the error's value,
+    /// rather than a finally block, which raises it again.
+    catches: bool,
+}
+
+/// Runs bytecode from the `Compiler` on a stack of values. It shares the
+/// tree-walking interpreter's globals, natives and modules, so the two run
+/// the same programs the same way.
+pub struct Vm<'a> {
+    interpreter: &'a mut Interpreter,
+    stack: Vec<Literal>,
+    frames: Vec<Frame>,
+    handlers: Vec<Handler>,
+    /// Errors set aside while the finally block of the try they were raised
+    /// in runs.
+    caught: Vec<RuntimeError>,
+    /// Upvalues still pointing into the stack, by slot.
+    open_upvalues: Vec<Rc<RefCell<Upvalue>>>,
+    /// Calls to Lux functions in progress, which `MAX_CALL_DEPTH` limits.
+    depth: usize,
+}
+
+impl<'a> Vm<'a> {
+    pub fn new(interpreter: &'a mut Interpreter) -> Self {
+        Vm {
+            interpreter,
+            stack: Vec::new(),
+            frames: Vec::new(),
+            handlers: Vec::new(),
+            caught: Vec::new(),
+            open_upvalues: Vec::new(),
+            depth: 0,
+        }
+    }
+
+    /// Runs a compiled script with the interpreter's globals.
+    pub fn run(&mut self, function: Rc<FunctionProto>) -> RuntimeResult<()> {
+        let globals = self.interpreter.globals.clone();
+        self.run_script(function, globals)
+    }
+
+    fn run_script(
+        &mut self,
+        function: Rc<FunctionProto>,
+        globals: Rc<RefCell<Environment>>,
+    ) -> RuntimeResult<()> {
+        let closure = Rc::new(Closure {
+            function,
+            upvalues: Vec::new(),
+            globals,
+        });
+        self.stack.push(Literal::Closure(closure.clone()));
+        self.frames.push(Frame {
+            closure,
+            ip: 0,
+            base: self.stack.len() - 1,
+        });
+        self.execute(self.frames.len() - 1).map(|_| ())
+    }
+
+    // Runs until the frame at `base` returns.
+    fn execute(&mut self, base: usize) -> RuntimeResult<Literal> {
+        loop {
+            match self.step(base) {
+                Ok(Some(value)) => return Ok(value),
+                Ok(None) => {}
+                Err(error) => self.catch(error, base)?,
+            }
+        }
+    }
+
+    // Runs one instruction, and returns the value the frame at `base`
+    // returned if it did.
+    fn step(&mut self, base: usize) -> RuntimeResult<Option<Literal>> {
+        let op = OpCode::from_byte(self.read_byte());
+        match op {
+            OpCode::Constant => {
+                let index = self.read_u16();
+                let value = self.closure().function.chunk.constants[index].clone();
+                self.push(value);
+            }
+            OpCode::Nil => self.push(Literal::Nil),
+            OpCode::True => self.push(Literal::Bool(true)),
+            OpCode::False => self.push(Literal::Bool(false)),
+            OpCode::Pop => {
+                self.pop();
+            }
+            OpCode::GetLocal => {
+                let slot = self.frame().base + self.read_u16();
+                self.push(self.stack[slot].clone());
+            }
+            OpCode::SetLocal => {
+                let slot = self.frame().base + self.read_u16();
+                self.stack[slot] = self.peek(0).clone();
+            }
+            OpCode::GetGlobal => {
+                let closure = self.closure();
+                let name = string(&closure, self.read_u16());
+                let value = closure.globals.borrow().lookup(name);
+                match value {
+                    Some(value) => self.push(value),
+                    None => return Err(self.undefined(&closure, name)),
+                }
+            }
+            OpCode::DefineGlobal => {
+                let closure = self.closure();
+                let name = string(&closure, self.read_u16());
+                let value = self.pop();
+                closure.globals.borrow_mut().define(name.to_string(), value);
+            }
+            OpCode::SetGlobal => {
+                let closure = self.closure();
+                let name = string(&closure, self.read_u16());
+                let value = self.peek(0).clone();
+                let assigned = closure.globals.borrow_mut().update(name, value);
+                if !assigned {
+                    return Err(self.undefined(&closure, name));
+                }
+            }
+            OpCode::GetUpvalue => {
+                let index = self.read_u16();
+                let closure = self.closure();
+                let value = match &*closure.upvalues[index].borrow() {
+                    Upvalue::Open(slot) => self.stack[*slot].clone(),
+                    Upvalue::Closed(value) => value.clone(),
+                };
+                self.push(value);
+            }
+            OpCode::SetUpvalue => {
+                let index = self.read_u16();
+                let value = self.peek(0).clone();
+                let closure = self.closure();
+                let mut upvalue = closure.upvalues[index].borrow_mut();
+                match &mut *upvalue {
+                    Upvalue::Open(slot) => self.stack[*slot] = value,
+                    Upvalue::Closed(closed) => *closed = value,
+                }
+            }
+            OpCode::CloseUpvalue => {
+                self.close_upvalues(self.stack.len() - 1);
+                self.pop();
+            }
+            OpCode::GetProperty => {
+                let closure = self.closure();
+                let name = string(&closure, self.read_u16());
+                let object = self.pop();
+                let value = match &object {
+                    Literal::VmInstance(instance) => {
+                        let field = instance.fields.borrow().get(name).cloned();
+                        match field {
+                            Some(value) => value,
+                            None => match instance.class.find_method(name) {
+                                Some(method) => bind(object.clone(), method),
+                                None => {
+                                    return Err(self.error(
+                                        ErrorCode::UndefinedMember,
+                                        format!("Undefined property '{}'.", name),
+                                    ))
+                                }
+                            },
+                        }
+                    }
+                    Literal::Error(error) => error.get(&self.location().token)?,
+                    Literal::Module(module) => module.get(&self.location().token)?,
+                    _ => {
+                        return Err(self.error(
+                            ErrorCode::WrongType,
+                            "Only instances have properties.".to_string(),
+                        ))
+                    }
+                };
+                self.push(value);
+            }
+            OpCode::SetProperty => {
+                let closure = self.closure();
+                let name = string(&closure, self.read_u16());
+                let value = self.pop();
+                match self.pop() {
+                    Literal::VmInstance(instance) => {
+                        instance
+                            .fields
+                            .borrow_mut()
+                            .insert(name.to_string(), value.clone());
+                        self.push(value);
+                    }
+                    _ => {
+                        return Err(self.error(
+                            ErrorCode::WrongType,
+                            "Only instances have fields.".to_string(),
+                        ))
+                    }
+                }
+            }
+            OpCode::GetSuper => {
+                let closure = self.closure();
+                let name = string(&closure, self.read_u16());
+                let superclass = self.pop();
+                let this = self.pop();
+                let method = match &superclass {
+                    Literal::VmClass(class) => class.find_method(name),
+                    _ => None,
+                };
+                match method {
+                    Some(method) => self.push(bind(this, method)),
+                    None => {
+                        return Err(self.error(
+                            ErrorCode::UndefinedMember,
+                            format!("Undefined property '{}'.", name),
+                        ))
+                    }
+                }
+            }
+            OpCode::GetIndex => {
+                let index = self.pop();
+                let value = match self.pop() {
+                    Literal::List(list) => list.get(&index, &self.location().token)?,
+                    Literal::Map(map) => map.get(&index, &self.location().token)?,
+                    _ => {
+                        return Err(self.error(
+                            ErrorCode::WrongType,
+                            "Only lists and maps can be indexed.".to_string(),
+                        ))
+                    }
+                };
+                self.push(value);
+            }
+            OpCode::SetIndex => {
+                let value = self.pop();
+                let index = self.pop();
+                match self.pop() {
+                    Literal::List(list) => {
+                        list.set(&index, value.clone(), &self.location().token)?
+                    }
+                    Literal::Map(map) => map.set(index, value.clone(), &self.location().token)?,
+                    _ => {
+                        return Err(self.error(
+                            ErrorCode::WrongType,
+                            "Only lists and maps can be indexed.".to_string(),
+                        ))
+                    }
+                }
+                self.push(value);
+            }
+            OpCode::Equal => {
+                let right = self.pop();
+                let left = self.pop();
+                self.push(Literal::Bool(left == right));
+            }
+            OpCode::Greater => self.compare(|left, right| left > right)?,
+            OpCode::GreaterEqual => self.compare(|left, right| left >= right)?,
+            OpCode::Less => self.compare(|left, right| left < right)?,
+            OpCode::LessEqual => self.compare(|left, right| left <= right)?,
+            OpCode::Add => {
+                let right = self.pop();
+                let left = self.pop();
+                let value = match (left, right) {
+                    (Literal::Number(left), Literal::Number(right)) => {
+                        Literal::Number(left + right)
+                    }
+                    (Literal::String(left), Literal::String(right)) => {
+                        Literal::String(left + &right)
+                    }
+                    _ => {
+                        return Err(self.error(
+                            ErrorCode::WrongType,
+                            "Operands must be two numbers or two strings.".to_string(),
+                        ))
+                    }
+                };
+                self.push(value);
+            }
+            OpCode::Subtract => self.arithmetic(|left, right| left - right)?,
+            OpCode::Multiply => self.arithmetic(|left, right| left * right)?,
+            OpCode::Divide => self.arithmetic(|left, right| left / right)?,
+            OpCode::Not => {
+                let value = self.pop();
+                self.push(Literal::Bool(!value.is_truthy()));
+            }
+            OpCode::Negate => match self.pop() {
+                Literal::Number(value) => self.push(Literal::Number(-value)),
+                _ => {
+                    return Err(self.error(
+                        ErrorCode::WrongType,
+                        "Operand must be a number.".to_string(),
+                    ))
+                }
+            },
+            OpCode::Print => println!("{}", self.pop()),
+            OpCode::Jump => {
+                let offset = self.read_jump();
+                self.frame_mut().ip += offset;
+            }
+            OpCode::JumpIfFalse => {
+                let offset = self.read_jump();
+                if !self.peek(0).is_truthy() {
+                    self.frame_mut().ip += offset;
+                }
+            }
+            OpCode::Loop => {
+                let offset = self.read_jump();
+                self.frame_mut().ip -= offset;
+            }
+            OpCode::Call => {
+                let count = usize::from(self.read_byte());
+                self.call(count)?;
+            }
+            OpCode::Closure => {
+                let closure = self.closure();
+                let function = closure.function.chunk.functions[self.read_u16()].clone();
+                let mut upvalues = Vec::with_capacity(function.upvalues);
+                for _ in 0..function.upvalues {
+                    let is_local = self.read_byte() == 1;
+                    let index = self.read_u16();
+                    if is_local {
+                        let slot = self.frame().base + index;
+                        upvalues.push(self.capture_upvalue(slot));
+                    } else {
+                        upvalues.push(closure.upvalues[index].clone());
+                    }
+                }
+                self.push(Literal::Closure(Rc::new(Closure {
+                    function,
+                    upvalues,
+                    globals: closure.globals.clone(),
+                })));
+            }
+            OpCode::Return => {
+                let value = self.pop();
+                let frame = self.frames.pop().expect("no frame to return from");
+                self.close_upvalues(frame.base);
+                self.stack.truncate(frame.base);
+                if frame.closure.function.kind != FunctionKind::Script {
+                    self.depth -= 1;
+                }
+                if self.frames.len() == base {
+                    return Ok(Some(value));
+                }
+                self.push(value);
+            }
+            OpCode::Class => {
+                let closure = self.closure();
+                let name = string(&closure, self.read_u16());
+                self.push(Literal::VmClass(Rc::new(VmClass::new(name.to_string()))));
+            }
+            OpCode::Inherit => {
+                let superclass = match self.peek(1) {
+                    Literal::VmClass(superclass) => superclass.clone(),
+                    _ => {
+                        return Err(self.error(
+                            ErrorCode::WrongType,
+                            "Superclass must be a class.".to_string(),
+                        ))
+                    }
+                };
+                if let Literal::VmClass(class) = self.pop() {
+                    let methods = superclass.methods.borrow().clone();
+                    class.methods.borrow_mut().extend(methods);
+                }
+            }
+            OpCode::Method => {
+                let closure = self.closure();
+                let name = string(&closure, self.read_u16());
+                let method = self.pop();
+                if let (Literal::VmClass(class), Literal::Closure(method)) = (self.peek(0), method)
+                {
+                    class.methods.borrow_mut().insert(name.to_string(), method);
+                }
+            }
+            OpCode::List => {
+                let count = self.read_u16();
+                let elements = self.stack.split_off(self.stack.len() - count);
+                self.push(Literal::List(LuxList::new(elements)));
+            }
+            OpCode::Map => {
+                let count = self.read_u16();
+                let mut entries = self
+                    .stack
+                    .split_off(self.stack.len() - 2 * count)
+                    .into_iter();
+                let map = LuxMap::new();
+                while let (Some(key), Some(value)) = (entries.next(), entries.next()) {
+                    map.set(key, value, &self.location().token)?;
+                }
+                self.push(Literal::Map(map));
+            }
+            OpCode::Interpolate => {
+                let count = self.read_u16();
+                let parts = self.stack.split_off(self.stack.len() - count);
+                let value: String = parts.iter().map(Literal::to_string).collect();
+                self.push(Literal::String(value));
+            }
+            OpCode::Throw => {
+                let value = self.pop();
+                let keyword = self.location().token.clone();
+                return Err(RuntimeError::thrown(keyword, value));
+            }
+            OpCode::PushCatch | OpCode::PushFinally => {
+                let offset = self.read_jump();
+                self.handlers.push(Handler {
+                    frame: self.frames.len() - 1,
+                    ip: self.frame().ip + offset,
+                    stack: self.stack.len(),
+                    caught: self.caught.len(),
+                    catches: op == OpCode::PushCatch,
+                });
+            }
+            OpCode::PopHandler => {
+                self.handlers.pop();
+            }
+            OpCode::Rethrow => {
+                let error = self.caught.pop().expect("no error to raise again");
+                return Err(error);
+            }
+            OpCode::DropCaught => {
+                self.caught.pop();
+            }
+            OpCode::Import => {
+                self.read_u16();
+                let module = self.import()?;
+                self.push(Literal::Module(Box::new(module)));
+            }
+            OpCode::ImportName => {
+                self.read_u16();
+                let value = match self.peek(0) {
+                    Literal::Module(module) => module.get(&self.location().token)?,
+                    _ => unreachable!("importing a name from a value that isn't a module"),
+                };
+                self.push(value);
+            }
+        }
+        Ok(None)
+    }
+
+    fn call(&mut self, count: usize) -> RuntimeResult<()> {
+        let slot = self.stack.len() - count - 1;
+        match self.stack[slot].clone() {
+            Literal::Closure(closure) => self.call_closure(closure, count),
+            Literal::BoundMethod(bound) => {
+                self.stack[slot] = bound.receiver.clone();
+                self.call_closure(bound.method.clone(), count)
+            }
+            Literal::VmClass(class) => {
+                let instance = Literal::VmInstance(Rc::new(VmInstance::new(class.clone())));
+                match class.find_method("init") {
+                    Some(init) => {
+                        self.check_arity(init.function.arity, count)?;
+                        self.stack[slot] = instance;
+                        self.call_closure(init, count)
+                    }
+                    None => {
+                        self.check_arity(0, count)?;
+                        self.stack[slot] = instance;
+                        Ok(())
+                    }
+                }
+            }
+            Literal::Native(native) => self.call_native(native.as_ref(), count),
+            Literal::Clock(clock) => self.call_native(&clock, count),
+            _ => {
+                let location = self.location();
+                Err(RuntimeError::new(
+                    location.token.clone(),
+                    ErrorCode::WrongType,
+                    "Can only call functions and classes.".to_string(),
+                )
+                .with_span(location.span))
+            }
+        }
+    }
+
+    fn call_closure(&mut self, closure: Rc<Closure>, count: usize) -> RuntimeResult<()> {
+        self.check_arity(closure.function.arity, count)?;
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(RuntimeError::new(
+                self.location().token.clone(),
+                ErrorCode::StackOverflow,
+                "Stack overflow.".to_string(),
+            ));
+        }
+        self.depth += 1;
+        self.frames.push(Frame {
+            closure,
+            ip: 0,
+            base: self.stack.len() - count - 1,
+        });
+        Ok(())
+    }
+
+    fn call_native(&mut self, function: &dyn LuxCallable, count: usize) -> RuntimeResult<()> {
+        self.check_arity(function.arity(), count)?;
+        let arguments = self.stack.split_off(self.stack.len() - count);
+        self.pop();
+        let closure = self.closure();
+        let paren = &closure.function.chunk.location(self.frame().ip).token;
+        let value = function.call(self.interpreter, arguments, paren)?;
+        self.push(value);
+        Ok(())
+    }
+
+    fn check_arity(&self, arity: usize, count: usize) -> RuntimeResult<()> {
+        if arity == count {
+            return Ok(());
+        }
+        // The location's span is the callee's; the error is about the whole
+        // call.
+        let location = self.location();
+        Err(RuntimeError::new(
+            location.token.clone(),
+            ErrorCode::WrongArity,
+            format!("Expected {} arguments but got {}.", arity, count),
+        )
+        .with_span(location.span.to(location.token.span)))
+    }
+
+    fn import(&mut self) -> RuntimeResult<module::LuxModule> {
+        let closure = self.closure();
+        let path = &closure.function.chunk.location(self.frame().ip).token;
+        let (canonical, statements, source) =
+            match ModuleLoader::begin_import(self.interpreter, path)? {
+                module::Import::Loaded(module) => return Ok(module),
+                module::Import::Run {
+                    canonical,
+                    statements,
+                    source,
+                } => (canonical, statements, source),
+            };
+
+        // The module runs with its own globals, as a script of its own.
+        let globals = Interpreter::new_globals();
+        let importer = ModuleLoader::enter_module(self.interpreter, &canonical);
+        let result = match Compiler::compile(&statements, self.interpreter.file.clone(), &source) {
+            Ok(function) => self.run_script(function, globals.clone()),
+            Err(err) => Err(RuntimeError::new(path.clone(), err.code, err.message)),
+        };
+        ModuleLoader::leave_module(self.interpreter, importer);
+
+        ModuleLoader::finish_import(self.interpreter, canonical, globals, result)
+    }
+
+    // Sends an error to the innermost handler installed since the frame at
+    // `base` started, or returns it if there is none.
+    fn catch(&mut self, error: RuntimeError, base: usize) -> RuntimeResult<()> {
+        let error = error.in_file(&self.frame().closure.function.file);
+        let handler = match self.handlers.last() {
+            Some(handler) if handler.frame >= base => self.handlers.pop().unwrap(),
+            _ => return Err(self.unwind(base, error)),
+        };
+
+        let error = self.unwind(handler.frame + 1, error);
+        self.close_upvalues(handler.stack);
+        self.stack.truncate(handler.stack);
+        self.caught.truncate(handler.caught);
+        self.frame_mut().ip = handler.ip;
+        if handler.catches {
+            self.push(error.into_value());
+        } else {
+            self.caught.push(error);
+        }
+        Ok(())
+    }
+
+    // Pops the frames above the first `keep`, recording the calls in progress
+    // on the error if it is leaving a function.
+    fn unwind(&mut self, keep: usize, mut error: RuntimeError) -> RuntimeError {
+        let leaves_function = self.frames[keep..]
+            .iter()
+            .any(|frame| frame.closure.function.kind != FunctionKind::Script);
+        if leaves_function && error.trace.is_empty() {
+            error.trace = self.trace(error.token.line);
+        }
+
+        while self.frames.len() > keep {
+            let frame = self.frames.pop().unwrap();
+            if frame.closure.function.kind != FunctionKind::Script {
+                self.depth -= 1;
+            }
+            if self.frames.len() == keep {
+                self.close_upvalues(frame.base);
+                self.stack.truncate(frame.base);
+            }
+        }
+        error
+    }
+
+    // The calls in progress, innermost first, down to the script or module
+    // they were made from.
+    fn trace(&self, line: usize) -> Vec<TraceLine> {
+        let mut trace = Vec::new();
+        for (index, frame) in self.frames.iter().rev().enumerate() {
+            let function = &frame.closure.function;
+            let line = match index {
+                0 => line,
+                _ => function.chunk.location(frame.ip).token.line,
+            };
+            trace.push(TraceLine {
+                function: function.name(),
+                file: function.file.clone(),
+                line,
+            });
+            if function.kind == FunctionKind::Script {
+                break;
+            }
+        }
+        trace
+    }
+
+    fn capture_upvalue(&mut self, slot: usize) -> Rc<RefCell<Upvalue>> {
+        let index = self
+            .open_upvalues
+            .partition_point(|upvalue| open_slot(upvalue) < slot);
+        if let Some(upvalue) = self.open_upvalues.get(index) {
+            if open_slot(upvalue) == slot {
+                return upvalue.clone();
+            }
+        }
+        let upvalue = Rc::new(RefCell::new(Upvalue::Open(slot)));
+        self.open_upvalues.insert(index, upvalue.clone());
+        upvalue
+    }
+
+    // Closes the upvalues pointing at `from` and the slots above it.
+    fn close_upvalues(&mut self, from: usize) {
+        while let Some(upvalue) = self.open_upvalues.last() {
+            let slot = open_slot(upvalue);
+            if slot < from {
+                break;
+            }
+            *upvalue.borrow_mut() = Upvalue::Closed(self.stack[slot].clone());
+            self.open_upvalues.pop();
+        }
+    }
+
+    fn compare(&mut self, compare: fn(f64, f64) -> bool) -> RuntimeResult<()> {
+        let (left, right) = self.number_operands()?;
+        self.push(Literal::Bool(compare(left, right)));
+        Ok(())
+    }
+
+    fn arithmetic(&mut self, operate: fn(f64, f64) -> f64) -> RuntimeResult<()> {
+        let (left, right) = self.number_operands()?;
+        self.push(Literal::Number(Float(operate(left, right))));
+        Ok(())
+    }
+
+    fn number_operands(&mut self) -> RuntimeResult<(f64, f64)> {
+        let right = self.pop();
+        let left = self.pop();
+        match (left, right) {
+            (Literal::Number(Float(left)), Literal::Number(Float(right))) => Ok((left, right)),
+            _ => Err(self.error(
+                ErrorCode::WrongType,
+                "Operands must be numbers.".to_string(),
+            )),
+        }
+    }
+
+    fn undefined(&self, closure: &Closure, name: &str) -> RuntimeError {
+        let location = self.location();
+        let help = location
+            .help
+            .clone()
+            .or_else(|| closure.globals.borrow().suggest(name));
+        RuntimeError::new(
+            location.token.clone(),
+            ErrorCode::UndefinedVariable,
+            format!("Undefined variable '{}'.", name),
+        )
+        .with_help(help)
+    }
+
+    // An error about the running instruction.
+    fn error(&self, code: ErrorCode, message: String) -> RuntimeError {
+        let location = self.location();
+        RuntimeError::new(location.token.clone(), code, message).with_span(location.span)
+    }
+
+    fn location(&self) -> &Location {
+        let frame = self.frame();
+        frame.closure.function.chunk.location(frame.ip)
+    }
+
+    fn read_byte(&mut self) -> u8 {
+        let frame = self.frame_mut();
+        let byte = frame.closure.function.chunk.code[frame.ip];
+        frame.ip += 1;
+        byte
+    }
+
+    fn read_u16(&mut self) -> usize {
+        let frame = self.frame_mut();
+        let value = frame.closure.function.chunk.read_u16(frame.ip);
+        frame.ip += 2;
+        usize::from(value)
+    }
+
+    fn read_jump(&mut self) -> usize {
+        let frame = self.frame_mut();
+        let value = frame.closure.function.chunk.read_u32(frame.ip);
+        frame.ip += 4;
+        value as usize
+    }
+
+    fn frame(&self) -> &Frame {
+        self.frames.last().expect("no frame running")
+    }
+
+    fn frame_mut(&mut self) -> &mut Frame {
+        self.frames.last_mut().expect("no frame running")
+    }
+
+    fn closure(&self) -> Rc<Closure> {
+        self.frame().closure.clone()
+    }
+
+    fn push(&mut self, value: Literal) {
+        self.stack.push(value);
+    }
+
+    fn pop(&mut self) -> Literal {
+        self.stack.pop().expect("stack underflow")
+    }
+
+    fn peek(&self, distance: usize) -> &Literal {
+        &self.stack[self.stack.len() - 1 - distance]
+    }
+}
+
+fn bind(receiver: Literal, method: Rc<Closure>) -> Literal {
+    Literal::BoundMethod(Rc::new(BoundMethod { receiver, method }))
+}
+
+// The name constant at `index`.
+fn string(closure: &Closure, index: usize) -> &str {
+    match &closure.function.chunk.constants[index] {
+        Literal::String(name) => name,
+        constant => unreachable!("expected a name constant, found {:?}", constant),
+    }
+}
+
+fn open_slot(upvalue: &Rc<RefCell<Upvalue>>) -> usize {
+    match *upvalue.borrow() {
+        Upvalue::Open(slot) => slot,
+        Upvalue::Closed(_) => unreachable!("closed upvalues are not tracked"),
+    }
+}
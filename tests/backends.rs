@@ -0,0 +1,56 @@
+mod common;
+
+use common::{assert_output, run_with_args};
+
+// `common` runs every script on both backends and checks they agree; these
+// cover what the VM compiles differently from how the tree-walker runs it.
+
+#[test]
+fn finally_runs_before_jumps_out_of_try() {
+    assert_output(
+        "fun f() {\n  for (var i = 0; i < 4; i = i + 1) {\n    try {\n      if (i == 1) continue;\n      if (i == 2) break;\n      print i;\n    } finally {\n      print \"finally ${i}\";\n    }\n  }\n  try {\n    return \"returned\";\n  } finally {\n    print \"before return\";\n  }\n}\nprint f();\n",
+        "0\nfinally 0\nfinally 1\nfinally 2\nbefore return\nreturned\n",
+    );
+}
+
+#[test]
+fn errors_pass_through_finally_and_catch() {
+    assert_output(
+        "fun fail() {\n  throw \"inner\";\n}\nfun f() {\n  try {\n    fail();\n  } catch (e) {\n    print \"caught ${e}\";\n    try {\n      fail();\n    } finally {\n      print \"inner finally\";\n    }\n  } finally {\n    print \"outer finally\";\n  }\n}\ntry {\n  f();\n} catch (e) {\n  print \"f threw ${e}\";\n}\ntry {\n  nil.x;\n} catch (e) {\n  print e.message;\n}\n",
+        "caught inner\ninner finally\nouter finally\nf threw inner\nOnly instances have properties.\n",
+    );
+}
+
+#[test]
+fn classes_and_superclass_methods() {
+    assert_output(
+        "class A {\n  init(x) {\n    this.x = x;\n  }\n  name() {\n    return \"A ${this.x}\";\n  }\n}\nclass B < A {\n  init(x) {\n    super.init(x * 2);\n  }\n  name() {\n    return \"B, \" + super.name();\n  }\n}\nvar b = B(2);\nvar name = b.name;\nprint name();\nprint b.init(5).x;\nprint b;\n",
+        "B, A 4\n10\nB instance\n",
+    );
+}
+
+#[test]
+fn negation_and_comparison() {
+    assert_output(
+        "print !true;\nprint !nil;\nprint 1 != 2;\nprint nil or \"default\";\nprint 1 and 2;\n",
+        "false\ntrue\ntrue\ndefault\n2\n",
+    );
+}
+
+#[test]
+fn unknown_backend_is_a_usage_error() {
+    let run = run_with_args("print 1;\n", &["--backend=jit"]);
+    assert_eq!(run.code, 64);
+}
+
+// Bodies this long compile to more than 64 KiB of bytecode, further than a
+// two-byte offset could jump.
+#[test]
+fn jumps_over_large_bodies() {
+    let body = "x = x + 1;\n".repeat(12_000);
+    let source = format!(
+        "var x = 0;\nif (x == 0) {{\n{body}}} else {{\n{body}}}\nprint x;\nvar i = 0;\nwhile (i < 2) {{\n{body}i = i + 1;\n}}\nprint x;\ntry {{\n{body}throw \"done\";\n}} catch (e) {{\n  print e;\n}}\nprint x;\n",
+        body = body
+    );
+    assert_output(&source, "12000\n36000\ndone\n48000\n");
+}
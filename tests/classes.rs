@@ -0,0 +1,84 @@
+mod common;
+
+use common::{assert_output, assert_resolve_error, assert_runtime_error};
+
+#[test]
+fn fields_are_set_and_read_on_instances() {
+    assert_output(
+        "class Point {}\nvar p = Point();\np.x = 1;\np.y = p.x + 1;\nprint p.x;\nprint p.y;\nprint p;\nprint Point;\n",
+        "1\n2\nPoint instance\nPoint\n",
+    );
+}
+
+#[test]
+fn methods_see_their_instance_as_this() {
+    assert_output(
+        "class Counter {\n  add(n) {\n    this.count = this.count + n;\n    return this;\n  }\n}\nvar c = Counter();\nc.count = 0;\nc.add(2).add(3);\nprint c.count;\n",
+        "5\n",
+    );
+}
+
+#[test]
+fn init_runs_on_construction_and_returns_the_instance() {
+    assert_output(
+        "class Pair {\n  init(a, b) {\n    this.a = a;\n    this.b = b;\n  }\n}\nvar p = Pair(1, 2);\nprint p.a + p.b;\nprint p.init(3, 4) == p;\nprint p.a;\n",
+        "3\ntrue\n3\n",
+    );
+}
+
+#[test]
+fn bound_methods_keep_their_instance() {
+    assert_output(
+        "class Greeter {\n  init(name) {\n    this.name = name;\n  }\n  greet() {\n    return \"hi \" + this.name;\n  }\n}\nvar greet = Greeter(\"ann\").greet;\nprint greet;\nprint greet();\n",
+        "<fn greet>\nhi ann\n",
+    );
+}
+
+#[test]
+fn this_outside_a_class() {
+    assert_resolve_error("print this;\n", "Can't use 'this' outside of a class.");
+    assert_resolve_error(
+        "fun f() {\n  return this;\n}\n",
+        "Can't use 'this' outside of a class.",
+    );
+}
+
+#[test]
+fn methods_are_found_through_every_superclass() {
+    assert_output(
+        "class A {\n  name() {\n    return \"a\";\n  }\n  shout() {\n    return this.name() + \"!\";\n  }\n}\nclass B < A {}\nclass C < B {\n  name() {\n    return \"c\";\n  }\n}\nprint C().shout();\nprint B().shout();\n",
+        "c!\na!\n",
+    );
+}
+
+#[test]
+fn super_calls_the_superclass_method() {
+    assert_output(
+        "class A {\n  init(x) {\n    this.x = x;\n  }\n  describe() {\n    return \"A\";\n  }\n}\nclass B < A {\n  init(x) {\n    super.init(x * 2);\n  }\n}\nclass C < B {\n  describe() {\n    return \"C of \" + super.describe();\n  }\n}\nvar c = C(2);\nprint c.x;\nprint c.describe();\n",
+        "4\nC of A\n",
+    );
+}
+
+#[test]
+fn a_class_cant_inherit_from_itself() {
+    assert_resolve_error("class A < A {}\n", "A class can't inherit from itself.");
+}
+
+#[test]
+fn super_needs_a_superclass() {
+    assert_resolve_error(
+        "class A {\n  f() {\n    return super.f();\n  }\n}\n",
+        "Can't use 'super' in a class with no superclass.",
+    );
+    assert_resolve_error("print super.f;\n", "Can't use 'super' outside of a class.");
+}
+
+#[test]
+fn superclass_must_be_a_class() {
+    assert_runtime_error(
+        "var NotAClass = \"text\";\nprint 1;\nclass A < NotAClass {}\n",
+        "1\n",
+        "Superclass must be a class.",
+        3,
+    );
+}
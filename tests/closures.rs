@@ -0,0 +1,35 @@
+mod common;
+
+use common::assert_output;
+
+#[test]
+fn closures_keep_their_own_variables() {
+    assert_output(
+        "fun makeCounter() {\n  var i = 0;\n  fun count() {\n    i = i + 1;\n    return i;\n  }\n  return count;\n}\nvar a = makeCounter();\nvar b = makeCounter();\nprint a();\nprint a();\nprint b();\n",
+        "1\n2\n1\n",
+    );
+}
+
+#[test]
+fn closures_bind_to_the_declaration_in_scope() {
+    assert_output(
+        "var a = \"global\";\n{\n  fun showA() {\n    print a;\n  }\n  showA();\n  var a = \"block\";\n  showA();\n  print a;\n}\n",
+        "global\nglobal\nblock\n",
+    );
+}
+
+#[test]
+fn assignment_through_several_scopes() {
+    assert_output(
+        "fun outer() {\n  var x = \"outer\";\n  fun middle() {\n    var y = \"middle\";\n    fun inner() {\n      print x + \" \" + y;\n      x = \"changed\";\n    }\n    return inner;\n  }\n  middle()();\n  print x;\n}\nouter();\n{\n  var m = 1;\n  {\n    var n = 2;\n    {\n      print m + n;\n      m = 10;\n    }\n  }\n  print m;\n}\n",
+        "outer middle\nchanged\n3\n10\n",
+    );
+}
+
+#[test]
+fn each_loop_iteration_gets_fresh_variables() {
+    assert_output(
+        "var fs = [];\nfor (var k = 0; k < 3; k = k + 1) {\n  var j = k;\n  push(fs, fun() { return j; });\n}\nprint fs[0]() + fs[1]() * 10 + fs[2]() * 100;\n",
+        "210\n",
+    );
+}
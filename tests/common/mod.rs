@@ -0,0 +1,126 @@
+// Each test crate includes this module and uses only part of it.
+#![allow(dead_code)]
+
+use std::{
+    env, fs,
+    path::Path,
+    process::Command,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static SCRIPT_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, PartialEq)]
+pub struct Run {
+    pub stdout: String,
+    pub stderr: String,
+    pub code: i32,
+}
+
+/// Writes `source` to a temporary script and runs it with the `lux` binary.
+pub fn run(source: &str) -> Run {
+    run_with_args(source, &[])
+}
+
+/// Runs `source` and checks that it exits cleanly after printing `expected`.
+pub fn assert_output(source: &str, expected: &str) {
+    let run = run(source);
+    assert_eq!(run.code, 0, "unexpected exit code, output:\n{}", run.stdout);
+    assert_eq!(run.stdout, expected);
+}
+
+/// Runs `source` and checks that it prints `printed` and then fails with a
+/// runtime error reporting `message` on `line`.
+pub fn assert_runtime_error(source: &str, printed: &str, message: &str, line: usize) {
+    let run = run(source);
+    assert_eq!(
+        run.code, 70,
+        "unexpected exit code, stderr:\n{}",
+        run.stderr
+    );
+    assert!(
+        run.stdout.starts_with(printed),
+        "expected output to start with {:?}, got:\n{}",
+        printed,
+        run.stdout
+    );
+    assert!(
+        run.stderr.contains(message),
+        "expected {:?} in stderr:\n{}",
+        message,
+        run.stderr
+    );
+    assert!(
+        run.stderr.contains(&format!(".lux:{}:", line)),
+        "expected line {} in stderr:\n{}",
+        line,
+        run.stderr
+    );
+}
+
+/// Runs `source` and checks that the resolver rejects it with `message`
+/// before anything runs.
+pub fn assert_resolve_error(source: &str, message: &str) {
+    let run = run(source);
+    assert_eq!(
+        run.code, 75,
+        "unexpected exit code, stderr:\n{}",
+        run.stderr
+    );
+    assert!(
+        run.stderr.contains(message),
+        "expected {:?} in stderr:\n{}",
+        message,
+        run.stderr
+    );
+    assert_eq!(run.stdout, "");
+}
+
+/// Like `run`, passing `args` before the script.
+pub fn run_with_args(source: &str, args: &[&str]) -> Run {
+    let id = SCRIPT_COUNT.fetch_add(1, Ordering::SeqCst);
+    let path = env::temp_dir().join(format!("lux-test-{}-{}.lux", std::process::id(), id));
+    fs::write(&path, source).expect("Failed to write test script");
+    let run = run_on_both_backends(&path, args);
+    fs::remove_file(&path).ok();
+    run
+}
+
+/// Writes each `(name, source)` pair into a temporary directory and runs the
+/// first one, so scripts can import the others.
+pub fn run_files(files: &[(&str, &str)]) -> Run {
+    let id = SCRIPT_COUNT.fetch_add(1, Ordering::SeqCst);
+    let dir = env::temp_dir().join(format!("lux-test-{}-{}", std::process::id(), id));
+    fs::create_dir_all(&dir).expect("Failed to create test directory");
+    for (name, source) in files {
+        fs::write(dir.join(name), source).expect("Failed to write test script");
+    }
+    let run = run_on_both_backends(&dir.join(files[0].0), &[]);
+    fs::remove_dir_all(&dir).ok();
+    run
+}
+
+// Runs `script` with the tree-walker and with the VM, and checks that they
+// behave the same.
+fn run_on_both_backends(script: &Path, args: &[&str]) -> Run {
+    let tree = run_lux(script, args);
+    let mut vm_args = args.to_vec();
+    vm_args.push("--backend=vm");
+    let vm = run_lux(script, &vm_args);
+    assert_eq!(tree, vm, "the backends disagree");
+    tree
+}
+
+fn run_lux(script: &Path, args: &[&str]) -> Run {
+    let output = Command::new(env!("CARGO_BIN_EXE_lux"))
+        .args(args)
+        .arg(script)
+        .output()
+        .expect("Failed to run lux");
+
+    Run {
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        code: output.status.code().unwrap_or(-1),
+    }
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use common::{run, run_with_args};
+
+// The `line:column` a human-format diagnostic points at, and the lines
+// after it with the snippet and carets.
+fn human_location(source: &str) -> (String, String) {
+    let run = run(source);
+    let arrow = run
+        .stderr
+        .lines()
+        .find(|line| line.trim_start().starts_with("--> "))
+        .unwrap_or_else(|| panic!("no location in stderr:\n{}", run.stderr));
+    let location = arrow.rsplit(".lux:").next().unwrap().to_string();
+    let snippet = run.stderr.split_once(arrow).unwrap().1.to_string();
+    (location, snippet)
+}
+
+fn json_error(source: &str) -> String {
+    let run = run_with_args(source, &["--error-format=json"]);
+    assert_ne!(run.code, 0, "expected an error, stdout:\n{}", run.stdout);
+    run.stderr.lines().next().unwrap_or_default().to_string()
+}
+
+#[test]
+fn parse_error_points_at_the_token() {
+    let source = "var a = 1;\nvar b = a +;\n";
+    let (location, snippet) = human_location(source);
+    assert_eq!(location, "2:12");
+    assert_eq!(snippet, "\n  |\n2 | var b = a +;\n  |            ^\n");
+    assert!(
+        json_error(source).contains("\"line\":2,\"column\":12,\"span\":{\"start\":22,\"end\":23}")
+    );
+}
+
+#[test]
+fn columns_count_characters_and_spans_count_bytes() {
+    let source = "var s = \"日本\";  print -s;\n";
+    let (location, snippet) = human_location(source);
+    assert_eq!(location, "1:22");
+    assert_eq!(
+        snippet,
+        "\n  |\n1 | var s = \"日本\";  print -s;\n  |                      ^^\n"
+    );
+    assert!(
+        json_error(source).contains("\"line\":1,\"column\":22,\"span\":{\"start\":25,\"end\":27}")
+    );
+}
+
+#[test]
+fn spans_cover_whole_expressions() {
+    let source = "  undefinedName;\n";
+    let (location, _) = human_location(source);
+    assert_eq!(location, "1:3");
+    assert!(
+        json_error(source).contains("\"line\":1,\"column\":3,\"span\":{\"start\":2,\"end\":15}")
+    );
+
+    let source = "var x = 1;\nprint x +\n  \"é\" + nil;\n";
+    let (location, snippet) = human_location(source);
+    assert_eq!(location, "2:7");
+    assert!(
+        snippet.starts_with("\n  |\n2 | print x +\n  |       ^^^\n"),
+        "{}",
+        snippet
+    );
+    assert!(
+        json_error(source).contains("\"line\":2,\"column\":7,\"span\":{\"start\":17,\"end\":27}")
+    );
+}
+
+#[test]
+fn runtime_errors_point_into_the_function_body() {
+    let source = "fun f(a) {\n  return a * -\"x\";\n}\nf(1);\n";
+    let (location, snippet) = human_location(source);
+    assert_eq!(location, "2:14");
+    assert!(
+        snippet.starts_with("\n  |\n2 |   return a * -\"x\";\n  |              ^^^^\n"),
+        "{}",
+        snippet
+    );
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use common::run_with_args;
+
+fn disassemble(source: &str) -> String {
+    let run = run_with_args(source, &["--disassemble"]);
+    assert_eq!(run.code, 0, "unexpected exit code, output:\n{}", run.stdout);
+    run.stdout
+}
+
+#[test]
+fn lists_instructions_with_lines_and_jump_targets() {
+    assert_eq!(
+        disassemble("var x = 1;\nif (x > 0) print \"yes\";\n"),
+        "== <script> ==
+0000    1 Constant            0 1
+0003    | DefineGlobal        1 \"x\"
+0006    2 GetGlobal           1 \"x\"
+0009    | Constant            2 0
+0012    | Greater
+0013    | JumpIfFalse        10 -> 0028
+0018    | Pop
+0019    | Constant            3 \"yes\"
+0022    | Print
+0023    | Jump                1 -> 0029
+0028    | Pop
+0029    | Nil
+0030    | Return
+",
+    );
+}
+
+#[test]
+fn lists_nested_functions_after_the_script() {
+    let output =
+        disassemble("fun f(a) {\n  fun g() {\n    return a;\n  }\n  return g;\n}\nf(1);\n");
+    let headers: Vec<&str> = output
+        .lines()
+        .filter(|line| line.starts_with("=="))
+        .collect();
+    assert_eq!(headers, ["== <script> ==", "== <fn f> ==", "== <fn g> =="]);
+    assert!(output.contains("|   captures local 1\n"), "{}", output);
+    assert!(
+        output.contains("GetUpvalue          0 upvalue\n"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn does_not_run_the_script() {
+    let output = disassemble("print \"ran\";\nthrow \"error\";\n");
+    assert!(!output.contains("ran\n"), "{}", output);
+    assert!(output.contains("Throw\n"), "{}", output);
+}
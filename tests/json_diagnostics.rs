@@ -0,0 +1,102 @@
+mod common;
+
+use common::run_with_args;
+
+fn json_errors(source: &str, code: i32) -> Vec<String> {
+    let run = run_with_args(source, &["--error-format=json"]);
+    assert_eq!(
+        run.code, code,
+        "unexpected exit code, stderr:\n{}",
+        run.stderr
+    );
+    run.stderr.lines().map(str::to_string).collect()
+}
+
+#[test]
+fn syntax_errors_are_one_object_per_line() {
+    let errors = json_errors("print (1;\n@\n", 65);
+    assert_eq!(errors.len(), 2, "stderr:\n{:?}", errors);
+    assert!(errors[0].starts_with("{\"severity\":\"error\",\"code\":\"E0100\","));
+    assert!(errors[0].contains("\"line\":1,\"column\":9,\"span\":{\"start\":8,\"end\":9}"));
+    assert!(errors[1].contains("\"code\":\"E0001\""));
+    assert!(errors[1].contains("\"message\":\"Unexpected character '@'.\""));
+}
+
+#[test]
+fn resolve_error_keeps_its_exit_code() {
+    let errors = json_errors("return 1;\n", 75);
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("\"code\":\"E0202\""));
+}
+
+#[test]
+fn runtime_error_has_code_and_trace() {
+    let errors = json_errors("fun f() {\n  return 1 - \"a\\tb\";\n}\nf();\n", 70);
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("\"code\":\"E0302\""));
+    assert!(errors[0].contains("\"line\":2,\"column\":10"));
+    assert!(errors[0].contains("\"trace\":[{\"function\":\"f\","));
+}
+
+#[test]
+fn program_output_stays_on_stdout() {
+    let run = run_with_args(
+        "print \"ok\";\nthrow \"a \\\"quoted\\\" value\";\n",
+        &["--error-format=json"],
+    );
+    assert_eq!(run.code, 70);
+    assert_eq!(run.stdout, "ok\n");
+    assert!(run.stderr.contains("\"code\":\"E0307\""));
+    assert!(run
+        .stderr
+        .contains("\"message\":\"a \\\"quoted\\\" value\""));
+}
+
+fn code_of(error: &str) -> &str {
+    let start = error.find("\"code\":\"").expect("no code") + "\"code\":\"".len();
+    &error[start..start + 5]
+}
+
+// The code of the only error `source` reports, given the exit code it fails
+// with.
+fn json_code(source: &str, code: i32) -> String {
+    let errors = json_errors(source, code);
+    assert_eq!(errors.len(), 1, "stderr:\n{:?}", errors);
+    code_of(&errors[0]).to_string()
+}
+
+#[test]
+fn natives_given_the_wrong_type_report_wrong_type() {
+    assert_eq!(json_code("len(1);\n", 70), "E0302");
+    assert_eq!(json_code("push(1, 2);\n", 70), "E0302");
+    assert_eq!(json_code("pop(nil);\n", 70), "E0302");
+    assert_eq!(json_code("has([], 1);\n", 70), "E0302");
+    assert_eq!(json_code("remove(\"a\", 1);\n", 70), "E0302");
+    assert_eq!(json_code("keys(true);\n", 70), "E0302");
+}
+
+#[test]
+fn codes_follow_the_kind_of_error_not_its_wording() {
+    assert_eq!(json_code("var s = \"\\u0041\";\n", 65), "E0003");
+    assert_eq!(
+        json_code("var m = {\"a\": 1};\nprint m[\"b\"];\n", 70),
+        "E0304"
+    );
+    assert_eq!(json_code("print [1][3];\n", 70), "E0305");
+    assert_eq!(json_code("fun f(_a) {}\nf();\n", 70), "E0303");
+    assert_eq!(json_code("import \"missing.lux\";\n", 75), "E0207");
+    assert_eq!(
+        json_code("throw \"Undefined variable 'x'.\";\n", 70),
+        "E0307"
+    );
+}
+
+#[test]
+fn warnings_have_their_own_codes() {
+    let errors = json_errors(
+        "fun f(a) {\n  var b = 1;\n  return;\n  print 2;\n}\nfun g() {}\nprint f;\n",
+        0,
+    );
+    let codes: Vec<&str> = errors.iter().map(|error| code_of(error)).collect();
+    assert_eq!(codes, ["W0002", "W0001", "W0005", "W0003"]);
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::{assert_output, assert_runtime_error};
+
+#[test]
+fn fun_expressions_are_values() {
+    assert_output(
+        "var add = fun (a, b) { return a + b; };\nprint add(1, 2);\nprint add;\nprint (fun () {})();\n",
+        "3\n<lambda>\nnil\n",
+    );
+}
+
+#[test]
+fn arrow_lambda_called_right_away() {
+    assert_output(
+        "print ((a) => a + 1)(41);\nprint ((a, b) => a * b)(6, 7);\n",
+        "42\n42\n",
+    );
+}
+
+#[test]
+fn arrow_lambda_with_a_block_body() {
+    assert_output(
+        "var triple = (x) => {\n  var y = x * 3;\n  return y;\n};\nprint triple(2);\nprint triple;\nprint (() => {})();\n",
+        "6\n<lambda>\nnil\n",
+    );
+}
+
+#[test]
+fn lambdas_close_over_their_scope() {
+    assert_output(
+        "fun adder(n) {\n  return (x) => x + n;\n}\nvar addTwo = adder(2);\nprint addTwo(40);\n",
+        "42\n",
+    );
+}
+
+#[test]
+fn lambdas_check_their_arity() {
+    assert_runtime_error(
+        "var f = (a, b) => a;\nprint f(1, 2);\nf(1);\n",
+        "1\n",
+        "Expected 2 arguments but got 1.",
+        3,
+    );
+}
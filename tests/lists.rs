@@ -0,0 +1,82 @@
+mod common;
+
+use common::{assert_output, assert_runtime_error};
+
+#[test]
+fn indexing_reads_and_writes_elements() {
+    assert_output(
+        "var l = [1, \"two\", [3]];\nprint l[0];\nprint l[1];\nprint l[2][0];\nl[1] = 2;\nprint l;\n",
+        "1\ntwo\n3\n[1, 2, [3]]\n",
+    );
+}
+
+#[test]
+fn index_out_of_range() {
+    assert_runtime_error(
+        "var l = [1, 2];\nprint l[1];\nprint l[2];\n",
+        "2\n",
+        "List index 2 out of range for list of length 2.",
+        3,
+    );
+    assert_runtime_error(
+        "var l = [];\nl[0] = 1;\n",
+        "",
+        "List index 0 out of range for list of length 0.",
+        2,
+    );
+}
+
+#[test]
+fn index_must_be_a_non_negative_integer() {
+    for index in &["-1", "0.5", "\"a\"", "nil"] {
+        assert_runtime_error(
+            &format!("print [1, 2][{}];\n", index),
+            "",
+            "List index must be a non-negative integer.",
+            1,
+        );
+    }
+}
+
+#[test]
+fn push_and_pop_change_the_list_in_place() {
+    assert_output(
+        "var l = [1];\nvar alias = l;\npush(l, 2);\npush(alias, 3);\nprint l;\nprint pop(l);\nprint pop(l);\nprint alias;\n",
+        "[1, 2, 3]\n3\n2\n[1]\n",
+    );
+}
+
+#[test]
+fn pop_from_an_empty_list() {
+    assert_runtime_error(
+        "var l = [1];\nprint pop(l);\nprint pop(l);\n",
+        "1\n",
+        "Can't pop from an empty list.",
+        3,
+    );
+}
+
+#[test]
+fn len_counts_elements_entries_and_characters() {
+    assert_output(
+        "print len([]);\nprint len([1, [2, 3]]);\nprint len({\"a\": 1, \"b\": 2});\nprint len(\"héllo\");\n",
+        "0\n2\n2\n5\n",
+    );
+}
+
+#[test]
+fn list_natives_check_their_arguments() {
+    assert_runtime_error(
+        "push(1, 2);\n",
+        "",
+        "push() expects a list as its first argument.",
+        1,
+    );
+    assert_runtime_error("pop(\"x\");\n", "", "pop() expects a list.", 1);
+    assert_runtime_error(
+        "len(1);\n",
+        "",
+        "len() expects a list, a map or a string.",
+        1,
+    );
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::{assert_output, assert_resolve_error};
+
+#[test]
+fn break_and_continue_in_for() {
+    assert_output(
+        "for (var i = 0; i < 5; i = i + 1) {\n  if (i == 1) continue;\n  if (i == 3) break;\n  print i;\n}\n",
+        "0\n2\n",
+    );
+}
+
+#[test]
+fn break_and_continue_in_while() {
+    assert_output(
+        "var i = 0;\nwhile (i < 5) {\n  i = i + 1;\n  if (i == 2) continue;\n  if (i == 4) break;\n  print i;\n}\nprint i;\n",
+        "1\n3\n4\n",
+    );
+}
+
+#[test]
+fn continue_in_for_still_runs_the_increment() {
+    assert_output(
+        "var runs = 0;\nvar i = 0;\nfor (; i < 3; i = i + 1) {\n  runs = runs + 1;\n  {\n    var skipped = i;\n    continue;\n  }\n}\nprint i;\nprint runs;\n",
+        "3\n3\n",
+    );
+}
+
+#[test]
+fn break_and_continue_leave_only_the_innermost_loop() {
+    assert_output(
+        "for (var a = 0; a < 2; a = a + 1) {\n  var b = 0;\n  while (true) {\n    b = b + 1;\n    if (b == 1) continue;\n    if (b == 3) break;\n    print \"${a} ${b}\";\n  }\n}\n",
+        "0 2\n1 2\n",
+    );
+}
+
+#[test]
+fn break_outside_a_loop() {
+    assert_resolve_error("break;\n", "Can't use 'break' outside of a loop.");
+    assert_resolve_error(
+        "while (true) {\n  fun f() {\n    break;\n  }\n  f();\n}\n",
+        "Can't use 'break' outside of a loop.",
+    );
+}
+
+#[test]
+fn continue_outside_a_loop() {
+    assert_resolve_error("continue;\n", "Can't use 'continue' outside of a loop.");
+    assert_resolve_error(
+        "for (;;) {\n  var f = fun() {\n    continue;\n  };\n  f();\n}\n",
+        "Can't use 'continue' outside of a loop.",
+    );
+}
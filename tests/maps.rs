@@ -0,0 +1,79 @@
+mod common;
+
+use common::{assert_output, assert_runtime_error};
+
+#[test]
+fn maps_print_in_insertion_order() {
+    assert_output(
+        "var d = {\"b\": 1, \"a\": 2, 3: \"three\", true: \"yes\"};\nd[\"c\"] = 4;\nd[\"b\"] = 10;\nprint d;\nprint keys(d);\n",
+        "{b: 10, a: 2, 3: three, true: yes, c: 4}\n[b, a, 3, true, c]\n",
+    );
+}
+
+#[test]
+fn has_and_remove() {
+    assert_output(
+        "var d = {\"a\": 1, \"b\": 2, \"c\": 3};\nprint has(d, \"b\");\nprint remove(d, \"b\");\nprint has(d, \"b\");\nprint remove(d, \"b\");\nprint d;\nd[\"b\"] = 4;\nprint keys(d);\nprint d[\"c\"];\nprint len(d);\n",
+        "true\n2\nfalse\nnil\n{a: 1, c: 3}\n[a, c, b]\n3\n3\n",
+    );
+}
+
+#[test]
+fn zero_and_negative_zero_are_the_same_key() {
+    assert_output(
+        "var d = {};\nd[0] = \"zero\";\nprint d[-0];\nprint has(d, -0);\nd[-0] = \"still zero\";\nprint len(d);\nprint remove(d, -0);\nprint d;\n",
+        "zero\ntrue\n1\nstill zero\n{}\n",
+    );
+}
+
+#[test]
+fn nan_is_not_a_map_key() {
+    assert_runtime_error(
+        "var d = {};\nd[0/0] = 1;\n",
+        "",
+        "Map keys can't be NaN.",
+        2,
+    );
+    assert_runtime_error("print has({}, 0/0);\n", "", "Map keys can't be NaN.", 1);
+}
+
+#[test]
+fn missing_and_invalid_keys() {
+    assert_runtime_error(
+        "var d = {\"a\": 1};\nprint d[\"a\"];\nprint d[\"b\"];\n",
+        "1\n",
+        "Undefined key 'b'.",
+        3,
+    );
+    assert_runtime_error(
+        "var d = {};\nd[nil] = 1;\n",
+        "",
+        "Map keys must be strings, numbers or booleans.",
+        2,
+    );
+}
+
+#[test]
+fn map_literal_in_statement_position() {
+    assert_output(
+        "var k = \"a\";\n{k + \"b\": 1, 1 + 2: 3}[\"ab\"];\n{k + \"c\": 1};\n{ k; print k; }\n{ print {1 + 2: 3}[3]; }\n",
+        "a\n3\n",
+    );
+}
+
+// Telling a map from a block mustn't parse the braces' contents, or nested
+// blocks like these take time exponential in their depth.
+#[test]
+fn deeply_nested_blocks_parse_quickly() {
+    let depth = 40;
+    let mut source = String::from("fun f(g) { g(); }\n");
+    for _ in 0..depth {
+        source.push_str("if (true) { f(fun() { ");
+    }
+    source.push_str("print \"deep\";");
+    for _ in 0..depth {
+        source.push_str(" }); }");
+    }
+    source.push('\n');
+    assert_output(&source, "deep\n");
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use common::run_files;
+
+#[test]
+fn imported_members_are_namespaced() {
+    let run = run_files(&[
+        (
+            "main.lux",
+            "import \"counter.lux\" as counter;\nprint counter.next();\nprint counter.next();\n",
+        ),
+        (
+            "counter.lux",
+            "var count = 0;\nfun next() {\n  count = count + 1;\n  return count;\n}\n",
+        ),
+    ]);
+    assert_eq!(run.code, 0, "output:\n{}", run.stdout);
+    assert_eq!(run.stdout, "1\n2\n");
+}
+
+#[test]
+fn identical_expressions_in_different_modules_resolve_separately() {
+    // `x` is at the same line and column in both files, but at a different
+    // depth.
+    let run = run_files(&[
+        (
+            "main.lux",
+            "{ var x = 1; { print x; } }\nimport \"other.lux\" as other;\n",
+        ),
+        ("other.lux", "{ var x = 2;   print x; }\n"),
+    ]);
+    assert_eq!(run.code, 0, "output:\n{}", run.stdout);
+    assert_eq!(run.stdout, "1\n2\n");
+}
+
+#[test]
+fn import_cycles_are_errors() {
+    let run = run_files(&[
+        ("main.lux", "import \"a.lux\" as a;\nprint \"main\";\n"),
+        ("a.lux", "import \"b.lux\" as b;\nprint \"a\";\n"),
+        ("b.lux", "import \"a.lux\" as a;\nprint \"b\";\n"),
+    ]);
+    assert_eq!(run.code, 75, "stderr:\n{}", run.stderr);
+    assert_eq!(run.stdout, "");
+    assert!(
+        run.stderr
+            .contains("Import cycle detected: a.lux -> b.lux -> a.lux."),
+        "stderr:\n{}",
+        run.stderr
+    );
+
+    let run = run_files(&[("main.lux", "import \"main.lux\" as me;\n")]);
+    assert_eq!(run.code, 75, "stderr:\n{}", run.stderr);
+    assert!(
+        run.stderr
+            .contains("Import cycle detected: main.lux -> main.lux."),
+        "stderr:\n{}",
+        run.stderr
+    );
+}
+
+#[test]
+fn modules_run_once_however_often_they_are_imported() {
+    let run = run_files(&[
+        (
+            "main.lux",
+            "import \"counter.lux\" as first;\nimport \"other.lux\" as other;\nimport \"counter.lux\" as second;\nfirst.next();\nother.next();\nprint second.next();\n",
+        ),
+        ("other.lux", "import \"counter.lux\" as counter;\nvar next = counter.next;\n"),
+        (
+            "counter.lux",
+            "print \"loading counter\";\nvar count = 0;\nfun next() {\n  count = count + 1;\n  return count;\n}\n",
+        ),
+    ]);
+    assert_eq!(run.code, 0, "stderr:\n{}", run.stderr);
+    assert_eq!(run.stdout, "loading counter\n3\n");
+}
+
+#[test]
+fn from_import_binds_members_directly() {
+    let run = run_files(&[
+        (
+            "main.lux",
+            "from \"shapes.lux\" import area, unit;\nprint area(2);\nprint unit;\n",
+        ),
+        (
+            "shapes.lux",
+            "var unit = \"cm\";\nfun area(side) {\n  return side * side;\n}\n",
+        ),
+    ]);
+    assert_eq!(run.code, 0, "stderr:\n{}", run.stderr);
+    assert_eq!(run.stdout, "4\ncm\n");
+
+    let run = run_files(&[
+        ("main.lux", "from \"shapes.lux\" import perimeter;\n"),
+        ("shapes.lux", "fun area(side) {\n  return side * side;\n}\n"),
+    ]);
+    assert_eq!(run.code, 70, "stderr:\n{}", run.stderr);
+    assert!(
+        run.stderr
+            .contains("Module 'shapes.lux' has no member 'perimeter'."),
+        "stderr:\n{}",
+        run.stderr
+    );
+}
+
+#[test]
+fn every_syntax_error_in_an_imported_module_is_reported() {
+    let run = run_files(&[
+        (
+            "main.lux",
+            "import \"broken.lux\";\nprint \"unreachable\";\n",
+        ),
+        ("broken.lux", "var = 1;\nprint (2;\nprint 3 +;\n"),
+    ]);
+    assert_eq!(run.code, 65, "stderr:\n{}", run.stderr);
+    assert_eq!(run.stdout, "");
+    let errors: Vec<&str> = run
+        .stderr
+        .lines()
+        .filter_map(|line| line.strip_prefix("error: "))
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            "Expect a variable name.",
+            "Expect ')' after expression",
+            "Expect expression.",
+        ]
+    );
+    assert_eq!(run.stderr.matches("broken.lux:").count(), 3);
+    assert_eq!(run.stderr.matches("imported from").count(), 3);
+    assert!(!run.stderr.contains("more syntax errors"));
+}
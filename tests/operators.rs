@@ -0,0 +1,11 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn bang_negates_truthiness() {
+    let run =
+        run("print !true;\nprint !false;\nprint !nil;\nprint !0;\nprint !\"\";\nprint !!nil;\n");
+    assert_eq!(run.code, 0, "unexpected exit code, output:\n{}", run.stdout);
+    assert_eq!(run.stdout, "false\ntrue\ntrue\nfalse\nfalse\nfalse\n");
+}
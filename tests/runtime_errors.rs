@@ -0,0 +1,186 @@
+mod common;
+
+use common::{assert_runtime_error, run};
+
+#[test]
+fn error_at_top_level() {
+    assert_runtime_error(
+        "print 1;\nprint -\"x\";\nprint 2;\n",
+        "1\n",
+        "Operand must be a number.",
+        2,
+    );
+}
+
+#[test]
+fn error_in_block() {
+    assert_runtime_error(
+        "{\n  print 1;\n  print -\"x\";\n  print 2;\n}\nprint 3;\n",
+        "1\n",
+        "Operand must be a number.",
+        3,
+    );
+}
+
+#[test]
+fn error_in_nested_block() {
+    assert_runtime_error(
+        "{\n  {\n    var a = 1;\n    a();\n  }\n  print 2;\n}\n",
+        "",
+        "Can only call functions and classes.",
+        4,
+    );
+}
+
+#[test]
+fn error_in_function_body() {
+    assert_runtime_error(
+        "fun f() {\n  print \"in f\";\n  return -\"x\";\n}\nprint f();\nprint \"after\";\n",
+        "in f\n",
+        "Operand must be a number.",
+        3,
+    );
+}
+
+#[test]
+fn error_in_nested_function_call() {
+    assert_runtime_error(
+        "fun inner() {\n  return nope;\n}\nfun outer() {\n  inner();\n  print \"unreachable\";\n}\nouter();\n",
+        "",
+        "Undefined variable 'nope'.",
+        2,
+    );
+}
+
+#[test]
+fn error_in_while_body() {
+    assert_runtime_error(
+        "var i = 0;\nwhile (i < 3) {\n  print i;\n  if (i == 1) {\n    i = i + \"x\";\n  }\n  i = i + 1;\n}\n",
+        "0\n1\n",
+        "Operands must be two numbers or two strings.",
+        5,
+    );
+}
+
+#[test]
+fn error_in_for_body() {
+    assert_runtime_error(
+        "for (var i = 0;\n     i < 3;\n     i = i + 1) {\n  print i;\n  [1][i];\n}\n",
+        "0\n1\n",
+        "out of range",
+        5,
+    );
+}
+
+#[test]
+fn error_in_method() {
+    assert_runtime_error(
+        "class A {\n  run() {\n    return this.missing;\n  }\n}\nA().run();\nprint \"after\";\n",
+        "",
+        "Undefined property 'missing'.",
+        3,
+    );
+}
+
+#[test]
+fn error_in_initializer() {
+    assert_runtime_error(
+        "class A {\n  init() {\n    this.x = -nil;\n  }\n}\nvar a = A();\nprint \"after\";\n",
+        "",
+        "Operand must be a number.",
+        3,
+    );
+}
+
+#[test]
+fn error_in_closure() {
+    assert_runtime_error(
+        "fun make() {\n  return () => {\n    return 1 / \"x\";\n  };\n}\nvar f = make();\nf();\n",
+        "",
+        "Operands must be numbers.",
+        3,
+    );
+}
+
+#[test]
+fn error_in_if_branch() {
+    assert_runtime_error(
+        "if (true) {\n  print \"then\";\n  nil.field = 1;\n} else {\n  print \"else\";\n}\n",
+        "then\n",
+        "Only instances have fields.",
+        3,
+    );
+}
+
+#[test]
+fn successful_script_exits_cleanly() {
+    let run = run("fun f(x) {\n  {\n    return x + 1;\n  }\n}\nprint f(1);\n");
+    assert_eq!(run.code, 0);
+    assert_eq!(run.stdout, "2\n");
+}
+
+#[test]
+fn uncaught_throw_is_a_runtime_error() {
+    assert_runtime_error(
+        "fun f() {\n  throw \"boom\";\n}\nprint \"start\";\nf();\n",
+        "start\n",
+        "boom",
+        2,
+    );
+}
+
+#[test]
+fn caught_error_does_not_terminate() {
+    let run = run("try {\n  -nil;\n} catch (e) {\n  print e.line;\n}\nprint \"after\";\n");
+    assert_eq!(run.code, 0);
+    assert_eq!(run.stdout, "2\nafter\n");
+}
+
+#[test]
+fn stack_trace_lists_calls() {
+    let run = run(
+        "fun fib(n) {\n  if (n < 2) return -\"x\";\n  return fib(n - 1);\n}\nfun main() {\n  return fib(2);\n}\nmain();\n",
+    );
+    assert_eq!(run.code, 70);
+    let trace = "at fib (line 2)\n      at fib (line 3)\n      at main (line 6)\n      at <script> (line 8)\n";
+    assert!(run.stderr.contains(trace), "stderr:\n{}", run.stderr);
+}
+
+#[test]
+fn deep_recursion_is_a_stack_overflow_error() {
+    let run = run("fun f(n) {\n  return f(n + 1);\n}\nf(0);\n");
+    assert_eq!(run.code, 70);
+    assert!(
+        run.stderr.contains("Stack overflow."),
+        "stderr:\n{}",
+        run.stderr
+    );
+    assert!(run.stderr.contains("repeated"), "stderr:\n{}", run.stderr);
+}
+
+#[test]
+fn undefined_variable_suggests_a_close_name() {
+    let global =
+        run("var count = 1;\nfun f(total) {\n  return total + count;\n}\nprint f(cout);\n");
+    assert_eq!(global.code, 70);
+    assert!(
+        global.stderr.contains("= help: did you mean 'count'?"),
+        "stderr:\n{}",
+        global.stderr
+    );
+
+    let local = run("var count = 1;\nfun f(total) {\n  return totl + count;\n}\nprint f(1);\n");
+    assert_eq!(local.code, 70);
+    assert!(
+        local.stderr.contains("= help: did you mean 'total'?"),
+        "stderr:\n{}",
+        local.stderr
+    );
+}
+
+#[test]
+fn no_suggestion_for_unrelated_names() {
+    let run = run("var count = 1;\nprint xyzzy;\n");
+    assert_eq!(run.code, 70);
+    assert!(!run.stderr.contains("help:"), "stderr:\n{}", run.stderr);
+}
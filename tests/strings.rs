@@ -0,0 +1,86 @@
+mod common;
+
+use common::{assert_output, run};
+
+fn assert_syntax_error(source: &str, message: &str) {
+    let run = run(source);
+    assert_eq!(
+        run.code, 65,
+        "unexpected exit code, stderr:\n{}",
+        run.stderr
+    );
+    assert!(
+        run.stderr.contains(message),
+        "expected {:?} in stderr:\n{}",
+        message,
+        run.stderr
+    );
+}
+
+#[test]
+fn invalid_escape_is_a_syntax_error() {
+    assert_syntax_error("print \"a\\qb\";\n", "Invalid escape sequence '\\q'.");
+}
+
+#[test]
+fn invalid_unicode_escape_is_a_syntax_error() {
+    assert_syntax_error(
+        "print \"\\u{110000}\";\n",
+        "Invalid unicode escape '\\u{110000}'.",
+    );
+    assert_syntax_error("print \"\\u0041\";\n", "Expect '{' after '\\u'.");
+}
+
+#[test]
+fn unterminated_string_is_a_syntax_error() {
+    assert_syntax_error("print \"abc;\n", "Unterminated string");
+}
+
+#[test]
+fn interpolation_converts_any_value() {
+    assert_output(
+        "var name = \"lux\";\nprint \"hi ${name}!\";\nprint \"${1 + 2}${3 * 4}\";\nprint \"${nil} ${true} ${[1, 2]} ${len(\"abc\")}\";\n",
+        "hi lux!\n312\nnil true [1, 2] 3\n",
+    );
+}
+
+#[test]
+fn interpolation_nests_strings_and_braces() {
+    assert_output(
+        "var x = \"x\";\nprint \"outer ${\"inner ${x}\"} done\";\nprint \"${ {\"k\": [1, 2]}[\"k\"][1] }\";\nprint \"${\"}\"}\";\nprint \"\"\"a ${1 + 1} b\"\"\";\n",
+        "outer inner x done\n2\n}\na 2 b\n",
+    );
+}
+
+#[test]
+fn unterminated_interpolation_is_a_syntax_error() {
+    assert_syntax_error(
+        "print \"a ${1 +} b\";\n",
+        "Expect '}' after interpolated expression.",
+    );
+    assert_syntax_error("print \"a ${1\";\n", "Unterminated string");
+}
+
+#[test]
+fn escape_sequences() {
+    assert_output(
+        "print \"tab\\tq\\\"uote\\\" back\\\\slash \\${ \\u{1F600} \\u{e9}\";\n",
+        "tab\tq\"uote\" back\\slash ${ 😀 é\n",
+    );
+}
+
+#[test]
+fn raw_strings_keep_backslashes_and_interpolation_markers() {
+    assert_output(
+        "print r\"C:\\new\\table ${x} \\u{41}\";\nprint r\"\"\"raw \"triple\" \\n\"\"\";\n",
+        "C:\\new\\table ${x} \\u{41}\nraw \"triple\" \\n\n",
+    );
+}
+
+#[test]
+fn triple_quoted_strings_span_lines_and_hold_quotes() {
+    assert_output(
+        "print \"\"\"line one\n  \"quoted\" line two\"\"\";\nprint \"after\";\n",
+        "line one\n  \"quoted\" line two\nafter\n",
+    );
+}
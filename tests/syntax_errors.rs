@@ -0,0 +1,69 @@
+mod common;
+
+use common::run;
+
+// The messages of every error reported, in order.
+fn syntax_errors(source: &str) -> Vec<String> {
+    let run = run(source);
+    assert_eq!(
+        run.code, 65,
+        "unexpected exit code, stderr:\n{}",
+        run.stderr
+    );
+    assert_eq!(run.stdout, "", "nothing should run");
+    run.stderr
+        .lines()
+        .filter_map(|line| line.strip_prefix("error: "))
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn every_statement_with_an_error_is_reported() {
+    assert_eq!(
+        syntax_errors("print 1;\nvar = 2;\nprint (3;\nprint 4;\nfun (a {}\n"),
+        [
+            "Expect a variable name.",
+            "Expect ')' after expression",
+            "Expect ')' after parameters.",
+        ]
+    );
+}
+
+#[test]
+fn errors_inside_blocks_and_functions_are_all_reported() {
+    assert_eq!(
+        syntax_errors("fun f() {\n  var x = ;\n  print x\n}\n{\n  1 +;\n}\nprint f();\n"),
+        [
+            "Expect expression.",
+            "Expect ';' after value",
+            "Expect expression.",
+        ]
+    );
+}
+
+#[test]
+fn scanner_and_parser_errors_come_in_source_order() {
+    assert_eq!(
+        syntax_errors("print (1;\nvar s = \"\\q\";\n#\nprint 2 +;\n"),
+        [
+            "Expect ')' after expression",
+            "Invalid escape sequence '\\q'.",
+            "Unexpected character '#'.",
+            "Expect expression.",
+        ]
+    );
+}
+
+#[test]
+fn an_unexpected_character_is_reported_once() {
+    assert_eq!(
+        syntax_errors("var x = @;\nprint 1 @ 2;\n{ print 3 @ }\n"),
+        [
+            "Unexpected character '@'.",
+            "Unexpected character '@'.",
+            "Unexpected character '@'.",
+        ]
+    );
+    assert_eq!(syntax_errors("print \"abc;\n"), ["Unterminated string"]);
+}
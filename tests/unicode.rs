@@ -0,0 +1,56 @@
+mod common;
+
+use common::{assert_output, run};
+
+#[test]
+fn emoji_in_strings() {
+    assert_output(
+        "print \"🎉 party 🦀\";\nprint \"a\" + \"🎉\" + \"b\";\n",
+        "🎉 party 🦀\na🎉b\n",
+    );
+}
+
+#[test]
+fn cjk_in_strings() {
+    assert_output(
+        "print \"你好，世界\";\nprint \"日本語\";\n",
+        "你好，世界\n日本語\n",
+    );
+}
+
+#[test]
+fn non_ascii_in_comments() {
+    assert_output(
+        "// 注释 with emoji 🎉 and ünïcödé\nprint 1; // 🦀 after code\nprint 2;\n",
+        "1\n2\n",
+    );
+}
+
+#[test]
+fn multi_line_string_keeps_line_numbers() {
+    let run = run("var s = \"第一行\n第二行 🎉\";\nprint s;\nprint -s;\n");
+    assert_eq!(run.code, 70);
+    assert!(run.stdout.starts_with("第一行\n第二行 🎉\n"));
+    assert!(run.stderr.contains(".lux:4:"), "stderr:\n{}", run.stderr);
+}
+
+#[test]
+fn unicode_identifiers() {
+    assert_output(
+        "var 名前 = \"世界\";\nvar café = 2;\nfun grüße(wer) { return \"hallo \" + wer; }\nprint 名前;\nprint café + 1;\nprint grüße(名前);\n",
+        "世界\n3\nhallo 世界\n",
+    );
+}
+
+#[test]
+fn interpolation_and_escapes() {
+    assert_output(
+        "var 名前 = \"世界\";\nprint \"こんにちは、${名前}! 🎉\";\nprint \"\\u{1F980} \\u{4E16}\";\n",
+        "こんにちは、世界! 🎉\n🦀 世\n",
+    );
+}
+
+#[test]
+fn length_counts_characters() {
+    assert_output("print len(\"🎉🎉\");\nprint len(\"世界\");\n", "2\n2\n");
+}
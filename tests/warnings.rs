@@ -0,0 +1,51 @@
+mod common;
+
+use common::{run, run_with_args};
+
+#[test]
+fn warnings_do_not_stop_the_script() {
+    let run = run("fun f(a) {\n  var b = 1;\n  return 2;\n}\nprint f(1);\n");
+    assert_eq!(run.code, 0, "stderr:\n{}", run.stderr);
+    assert_eq!(run.stdout, "2\n");
+    assert!(run.stderr.contains("warning: Unused parameter 'a'."));
+    assert!(run.stderr.contains("warning: Unused variable 'b'."));
+}
+
+#[test]
+fn underscore_names_are_not_reported() {
+    let run = run("fun _helper(_a) {\n  var _b = 1;\n}\n");
+    assert_eq!(run.code, 0);
+    assert_eq!(run.stderr, "");
+}
+
+#[test]
+fn shadowing_and_unreachable_code() {
+    let run = run(
+        "fun f(x) {\n  {\n    var x = 2;\n    print x;\n  }\n  return x;\n  print x;\n}\nf(1);\n",
+    );
+    assert!(run
+        .stderr
+        .contains("warning: Declaration of 'x' shadows a local variable from an enclosing scope."));
+    assert!(run.stderr.contains("warning: Unreachable code."));
+    assert!(run
+        .stderr
+        .contains("'return' on line 6 leaves the block before this runs"));
+}
+
+#[test]
+fn functions_never_called() {
+    let run = run("fun used() {}\nfun unused() {}\nused();\n");
+    assert!(!run.stderr.contains("'used'"));
+    assert!(run
+        .stderr
+        .contains("warning: Function 'unused' is never called."));
+}
+
+#[test]
+fn deny_warnings_fails_before_running() {
+    let run = run_with_args("print 1;\nfun unused() {}\n", &["--deny-warnings"]);
+    assert_eq!(run.code, 75);
+    assert!(run
+        .stderr
+        .starts_with("error: Function 'unused' is never called."));
+}